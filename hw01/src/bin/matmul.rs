@@ -0,0 +1,65 @@
+//! Multiplies two matrix files and writes the product.
+//!
+//! Usage: `matmul <lhs> <rhs> [output]`
+//!
+//! Files ending in `.mtx` use the Matrix Market format and anything else is
+//! read as CSV. Without an output path the product is printed as CSV.
+
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::process;
+
+use hw01::matrix_io::{self, MtxFormat};
+use hw01::problem2::Matrix;
+
+fn is_matrix_market(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "mtx")
+}
+
+fn read(path: &Path) -> Result<Matrix<f64>, Box<dyn Error>> {
+    let file = File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    let mat = if is_matrix_market(path) {
+        matrix_io::read_matrix_market(file)
+    } else {
+        matrix_io::read_csv(file)
+    };
+
+    mat.map_err(|err| format!("{}: {}", path.display(), err).into())
+}
+
+fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err("usage: matmul <lhs> <rhs> [output]".into());
+    }
+
+    let lhs = read(Path::new(&args[0]))?;
+    let rhs = read(Path::new(&args[1]))?;
+    let product = lhs.mat_mult(&rhs)?;
+
+    match args.get(2).map(Path::new) {
+        Some(path) => {
+            let out = BufWriter::new(File::create(path)?);
+            if is_matrix_market(path) {
+                matrix_io::write_matrix_market(&product, MtxFormat::Coordinate, out)?;
+            } else {
+                matrix_io::write_csv(&product, out)?;
+            }
+        }
+        None => matrix_io::write_csv(&product, io::stdout().lock())?,
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("matmul: {}", err);
+        process::exit(1);
+    }
+}
@@ -1,4 +1,7 @@
+pub mod linalg;
+pub mod matrix_io;
 pub mod problem1;
 pub mod problem2;
 pub mod problem3;
 pub mod problem4;
+pub mod sparse;
@@ -0,0 +1,286 @@
+use std::error;
+use std::fmt;
+use std::ops::{Div, Neg};
+
+use crate::problem2::{Matrix, Numeric};
+
+/// Floating point element types supported by the decomposition routines.
+pub trait Float: Numeric + PartialOrd + Div<Output = Self> + Neg<Output = Self> {
+    /// Returns the absolute value of `self`.
+    fn abs(self) -> Self;
+    /// The machine epsilon of the type.
+    fn epsilon() -> Self;
+    /// Converts a matrix dimension, used to scale the singularity tolerance.
+    fn from_usize(n: usize) -> Self;
+}
+
+macro_rules! impl_float {
+    ($($t:ident),*) => {
+        $(
+            impl Float for $t {
+                fn abs(self) -> $t {
+                    $t::abs(self)
+                }
+
+                fn epsilon() -> $t {
+                    $t::EPSILON
+                }
+
+                fn from_usize(n: usize) -> $t {
+                    n as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+/// An error produced by the linear algebra routines.
+pub enum LinalgError {
+    /// The operation requires a square matrix.
+    NotSquare { rows: usize, cols: usize },
+    /// The matrix has no inverse (up to floating point tolerance).
+    Singular,
+    /// The right-hand side does not have one entry per matrix row.
+    DimensionMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for LinalgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinalgError::NotSquare { rows, cols } => {
+                write!(f, "expected a square matrix, found {}x{}", rows, cols)
+            }
+            LinalgError::Singular => write!(f, "matrix is singular"),
+            LinalgError::DimensionMismatch { expected, actual } => write!(
+                f,
+                "expected a vector of length {}, found {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl error::Error for LinalgError {}
+
+/// Returns the transpose of `mat`.
+pub fn transpose<T: Numeric>(mat: &Matrix<T>) -> Matrix<T> {
+    let (rows, cols) = mat.shape();
+    let mut result = Matrix::zeros(cols, rows);
+
+    for i in 0..rows {
+        for (j, &x) in mat.row(i).iter().enumerate() {
+            result[(j, i)] = x;
+        }
+    }
+
+    result
+}
+
+/// An LU decomposition with partial pivoting, `P * A = L * U`.
+///
+/// `L` (unit lower triangular, diagonal not stored) and `U` share one matrix.
+#[derive(Clone, Debug)]
+pub struct Lu<T> {
+    lu: Matrix<T>,
+    perm: Vec<usize>,
+    swaps: usize,
+}
+
+/// Computes the LU decomposition of the square matrix `mat`.
+///
+/// The matrix counts as singular when a pivot is negligible next to the
+/// largest entry of its column, so rows and columns of very different
+/// magnitudes do not make it look singular.
+pub fn lu<T: Float>(mat: &Matrix<T>) -> Result<Lu<T>, LinalgError> {
+    let n = check_square(mat)?;
+    let tolerances = (0..n)
+        .map(|k| {
+            let scale = (0..n).fold(T::zero(), |max, i| {
+                let x = mat[(i, k)].abs();
+                if x > max {
+                    x
+                } else {
+                    max
+                }
+            });
+            T::epsilon() * T::from_usize(n) * scale
+        })
+        .collect::<Vec<_>>();
+
+    decompose(mat, &tolerances)
+}
+
+/// Runs Gaussian elimination with partial pivoting on the square matrix
+/// `mat`, failing when the pivot of column `k` is at most `tolerances[k]`.
+fn decompose<T: Float>(mat: &Matrix<T>, tolerances: &[T]) -> Result<Lu<T>, LinalgError> {
+    let n = mat.rows();
+    let mut lu = mat.clone();
+    let mut perm: Vec<usize> = (0..n).collect();
+    let mut swaps = 0;
+
+    for k in 0..n {
+        // Pick the largest remaining entry of column `k` as the pivot.
+        let mut pivot = k;
+        for i in k + 1..n {
+            if lu[(i, k)].abs() > lu[(pivot, k)].abs() {
+                pivot = i;
+            }
+        }
+
+        if lu[(pivot, k)].abs() <= tolerances[k] {
+            return Err(LinalgError::Singular);
+        }
+
+        if pivot != k {
+            for j in 0..n {
+                let tmp = lu[(k, j)];
+                lu[(k, j)] = lu[(pivot, j)];
+                lu[(pivot, j)] = tmp;
+            }
+            perm.swap(k, pivot);
+            swaps += 1;
+        }
+
+        for i in k + 1..n {
+            let factor = lu[(i, k)] / lu[(k, k)];
+            lu[(i, k)] = factor;
+
+            for j in k + 1..n {
+                lu[(i, j)] = lu[(i, j)] - factor * lu[(k, j)];
+            }
+        }
+    }
+
+    Ok(Lu { lu, perm, swaps })
+}
+
+impl<T: Float> Lu<T> {
+    /// Returns the unit lower triangular factor `L`.
+    pub fn l(&self) -> Matrix<T> {
+        let n = self.lu.rows();
+        let mut l = Matrix::identity(n);
+
+        for i in 0..n {
+            for j in 0..i {
+                l[(i, j)] = self.lu[(i, j)];
+            }
+        }
+
+        l
+    }
+
+    /// Returns the upper triangular factor `U`.
+    pub fn u(&self) -> Matrix<T> {
+        let n = self.lu.rows();
+        let mut u = Matrix::zeros(n, n);
+
+        for i in 0..n {
+            for j in i..n {
+                u[(i, j)] = self.lu[(i, j)];
+            }
+        }
+
+        u
+    }
+
+    /// Returns the row permutation: row `i` of `P * A` is row `perm[i]` of
+    /// `A`.
+    pub fn permutation(&self) -> &[usize] {
+        &self.perm
+    }
+
+    /// Returns the determinant of the decomposed matrix.
+    pub fn determinant(&self) -> T {
+        let det = (0..self.lu.rows()).fold(T::one(), |det, i| det * self.lu[(i, i)]);
+
+        if self.swaps.is_multiple_of(2) {
+            det
+        } else {
+            -det
+        }
+    }
+
+    /// Solves `A * x = b` for `x`.
+    pub fn solve(&self, b: &[T]) -> Result<Vec<T>, LinalgError> {
+        let n = self.lu.rows();
+
+        if b.len() != n {
+            return Err(LinalgError::DimensionMismatch {
+                expected: n,
+                actual: b.len(),
+            });
+        }
+
+        // Forward substitution with `L`, then back substitution with `U`.
+        let mut x: Vec<T> = self.perm.iter().map(|&i| b[i]).collect();
+
+        for i in 0..n {
+            for j in 0..i {
+                x[i] = x[i] - self.lu[(i, j)] * x[j];
+            }
+        }
+
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                x[i] = x[i] - self.lu[(i, j)] * x[j];
+            }
+            x[i] = x[i] / self.lu[(i, i)];
+        }
+
+        Ok(x)
+    }
+
+    /// Returns the inverse of the decomposed matrix.
+    pub fn inverse(&self) -> Matrix<T> {
+        let n = self.lu.rows();
+        let mut inv = Matrix::zeros(n, n);
+        let mut e = vec![T::zero(); n];
+
+        for j in 0..n {
+            e[j] = T::one();
+            let col = self.solve(&e).expect("unit vector has matching length");
+            e[j] = T::zero();
+
+            for (i, x) in col.into_iter().enumerate() {
+                inv[(i, j)] = x;
+            }
+        }
+
+        inv
+    }
+}
+
+/// Computes the determinant of the square matrix `mat`, as the product of
+/// the pivots. It is only zero when elimination hits an exactly zero pivot.
+pub fn determinant<T: Float>(mat: &Matrix<T>) -> Result<T, LinalgError> {
+    let n = check_square(mat)?;
+
+    match decompose(mat, &vec![T::zero(); n]) {
+        Ok(lu) => Ok(lu.determinant()),
+        Err(LinalgError::Singular) => Ok(T::zero()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Computes the inverse of the square matrix `mat`.
+pub fn inverse<T: Float>(mat: &Matrix<T>) -> Result<Matrix<T>, LinalgError> {
+    lu(mat).map(|lu| lu.inverse())
+}
+
+/// Solves the linear system `a * x = b` for `x`.
+pub fn solve<T: Float>(a: &Matrix<T>, b: &[T]) -> Result<Vec<T>, LinalgError> {
+    lu(a).and_then(|lu| lu.solve(b))
+}
+
+fn check_square<T: Numeric>(mat: &Matrix<T>) -> Result<usize, LinalgError> {
+    let (rows, cols) = mat.shape();
+
+    if rows != cols {
+        return Err(LinalgError::NotSquare { rows, cols });
+    }
+
+    Ok(rows)
+}
@@ -0,0 +1,460 @@
+use std::error;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+use crate::problem2::{Matrix, Numeric};
+
+/// Element types that can be read from and written to text files.
+pub trait Element: Numeric + FromStr + fmt::Display {
+    /// The Matrix Market field name for the type.
+    const FIELD: &'static str;
+}
+
+impl Element for f32 {
+    const FIELD: &'static str = "real";
+}
+
+impl Element for f64 {
+    const FIELD: &'static str = "real";
+}
+
+impl Element for i32 {
+    const FIELD: &'static str = "integer";
+}
+
+impl Element for i64 {
+    const FIELD: &'static str = "integer";
+}
+
+#[derive(Debug)]
+/// An error produced while reading or writing a matrix file.
+pub enum MatrixIoError {
+    /// The underlying reader or writer failed.
+    Io(io::Error),
+    /// The input is malformed at the given line and column, both 1-based.
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for MatrixIoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MatrixIoError::Io(err) => write!(f, "{}", err),
+            MatrixIoError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl error::Error for MatrixIoError {}
+
+impl From<io::Error> for MatrixIoError {
+    fn from(err: io::Error) -> MatrixIoError {
+        MatrixIoError::Io(err)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// The layout used when writing a Matrix Market file.
+pub enum MtxFormat {
+    /// One `row col value` line per non-zero entry.
+    Coordinate,
+    /// Every entry, in column-major order.
+    Array,
+}
+
+fn parse_error(line: usize, column: usize, message: String) -> MatrixIoError {
+    MatrixIoError::Parse {
+        line,
+        column,
+        message,
+    }
+}
+
+/// Splits `line` into the tokens separated by `sep`, paired with the 1-based
+/// column, counted in characters, where each one starts. Surrounding
+/// whitespace is trimmed.
+fn split_columns<'a>(
+    line: &'a str,
+    sep: &'a dyn Fn(char) -> bool,
+) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    let mut offset = 0;
+
+    line.split(sep).map(move |field| {
+        let width = field.chars().count();
+        let start = offset + width - field.trim_start().chars().count();
+        offset += width + 1;
+        (start + 1, field.trim())
+    })
+}
+
+fn parse_value<T: FromStr>(token: &str, line: usize, column: usize) -> Result<T, MatrixIoError> {
+    token
+        .parse()
+        .map_err(|_| parse_error(line, column, format!("invalid number `{}`", token)))
+}
+
+/// Parses a matrix from comma-separated values, one row per line. Blank lines
+/// are ignored.
+pub fn parse_csv<T: Element>(input: &str) -> Result<Matrix<T>, MatrixIoError> {
+    let mut data = vec![];
+    let mut cols = None;
+    let mut rows = 0;
+
+    for (i, text) in input.lines().enumerate() {
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        let mut count = 0;
+        for (column, token) in split_columns(text, &|c| c == ',') {
+            data.push(parse_value(token, i + 1, column)?);
+            count += 1;
+        }
+
+        match cols {
+            Some(n) if n != count => {
+                return Err(parse_error(
+                    i + 1,
+                    1,
+                    format!("expected {} fields, found {}", n, count),
+                ))
+            }
+            _ => cols = Some(count),
+        }
+        rows += 1;
+    }
+
+    Ok(Matrix::new(rows, cols.unwrap_or(0), data).expect("every row has `cols` elements"))
+}
+
+/// Reads a matrix in CSV format, see `parse_csv`.
+pub fn read_csv<T: Element, R: Read>(mut reader: R) -> Result<Matrix<T>, MatrixIoError> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    parse_csv(&input)
+}
+
+/// Writes `mat` as comma-separated values, one row per line.
+pub fn write_csv<T: Element, W: Write>(mat: &Matrix<T>, mut writer: W) -> io::Result<()> {
+    for i in 0..mat.rows() {
+        let row: Vec<String> = mat.row(i).iter().map(|x| x.to_string()).collect();
+        writeln!(writer, "{}", row.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Parses a matrix from the Matrix Market exchange format.
+///
+/// Both the `coordinate` and `array` layouts are supported, with `real` or
+/// `integer` fields and `general`, `symmetric` or `skew-symmetric` symmetry.
+pub fn parse_matrix_market<T: Element>(input: &str) -> Result<Matrix<T>, MatrixIoError> {
+    let mut lines = input.lines().enumerate().map(|(i, text)| (i + 1, text));
+    let whitespace = |c: char| c.is_whitespace();
+
+    let (_, header) = lines
+        .next()
+        .ok_or_else(|| parse_error(1, 1, "missing header".to_string()))?;
+    let fields: Vec<(usize, String)> = split_columns(header, &whitespace)
+        .filter(|(_, token)| !token.is_empty())
+        .map(|(column, token)| (column, token.to_lowercase()))
+        .collect();
+
+    if fields.len() != 5 || fields[0].1 != "%%matrixmarket" || fields[1].1 != "matrix" {
+        return Err(parse_error(
+            1,
+            1,
+            "expected `%%MatrixMarket matrix <format> <field> <symmetry>`".to_string(),
+        ));
+    }
+
+    let coordinate = match fields[2].1.as_str() {
+        "coordinate" => true,
+        "array" => false,
+        other => {
+            return Err(parse_error(
+                1,
+                fields[2].0,
+                format!("unsupported format `{}`", other),
+            ))
+        }
+    };
+    match fields[3].1.as_str() {
+        "real" | "integer" => (),
+        other => {
+            return Err(parse_error(
+                1,
+                fields[3].0,
+                format!("unsupported field `{}`", other),
+            ))
+        }
+    }
+    let symmetry = match fields[4].1.as_str() {
+        "general" => Symmetry::General,
+        "symmetric" => Symmetry::Symmetric,
+        "skew-symmetric" => Symmetry::SkewSymmetric,
+        other => {
+            return Err(parse_error(
+                1,
+                fields[4].0,
+                format!("unsupported symmetry `{}`", other),
+            ))
+        }
+    };
+
+    // Every remaining non-comment line is a list of whitespace-separated
+    // tokens; the first one holds the dimensions.
+    let mut records = lines
+        .filter(|(_, text)| !text.trim().is_empty() && !text.trim_start().starts_with('%'))
+        .map(|(line, text)| {
+            let tokens: Vec<(usize, &str)> = split_columns(text, &whitespace)
+                .filter(|(_, token)| !token.is_empty())
+                .collect();
+            (line, tokens)
+        });
+
+    let (size_line, size) = records
+        .next()
+        .ok_or_else(|| parse_error(1, 1, "missing size line".to_string()))?;
+    let expected = if coordinate { 3 } else { 2 };
+    if size.len() != expected {
+        return Err(parse_error(
+            size_line,
+            1,
+            format!("expected {} sizes, found {}", expected, size.len()),
+        ));
+    }
+    let sizes = size
+        .iter()
+        .map(|&(column, token)| parse_value::<usize>(token, size_line, column))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (rows, cols) = (sizes[0], sizes[1]);
+    let len = rows.checked_mul(cols).ok_or_else(|| {
+        parse_error(
+            size_line,
+            size[0].0,
+            format!("a {}x{} matrix has too many elements", rows, cols),
+        )
+    })?;
+
+    if symmetry != Symmetry::General && rows != cols {
+        return Err(parse_error(
+            size_line,
+            1,
+            "symmetric matrices must be square".to_string(),
+        ));
+    }
+
+    // The entries are collected before the matrix is allocated, so that a
+    // size line alone cannot make us allocate more than the input holds.
+    let mut entries: Vec<(usize, usize, T)> = vec![];
+
+    if coordinate {
+        let count = sizes[2];
+
+        for (line, tokens) in records {
+            if tokens.len() != 3 {
+                return Err(parse_error(
+                    line,
+                    1,
+                    format!("expected `row col value`, found {} fields", tokens.len()),
+                ));
+            }
+            if entries.len() == count {
+                return Err(parse_error(
+                    line,
+                    1,
+                    format!("more than {} entries", count),
+                ));
+            }
+
+            let index = |k: usize, bound: usize| {
+                let (column, token) = tokens[k];
+                let x: usize = parse_value(token, line, column)?;
+                if x == 0 || x > bound {
+                    Err(parse_error(
+                        line,
+                        column,
+                        format!("index {} is outside 1..={}", x, bound),
+                    ))
+                } else {
+                    Ok(x - 1)
+                }
+            };
+            let (i, j) = (index(0, rows)?, index(1, cols)?);
+            if !symmetry.stores(i, j) {
+                return Err(parse_error(
+                    line,
+                    tokens[0].0,
+                    format!(
+                        "entry ({}, {}) is not in the lower triangle stored by {} files",
+                        i + 1,
+                        j + 1,
+                        symmetry.name()
+                    ),
+                ));
+            }
+            let value = parse_value(tokens[2].1, line, tokens[2].0)?;
+
+            entries.push((i, j, value));
+        }
+
+        if entries.len() != count {
+            return Err(parse_error(
+                input.lines().count().max(1),
+                1,
+                format!("expected {} entries, found {}", count, entries.len()),
+            ));
+        }
+    } else {
+        // Array files list columns top to bottom; symmetric ones only hold
+        // the lower triangle (without the diagonal when skew-symmetric).
+        let mut positions = (0..cols).flat_map(|j| {
+            let first = match symmetry {
+                Symmetry::General => 0,
+                Symmetry::Symmetric => j,
+                Symmetry::SkewSymmetric => j + 1,
+            };
+            (first..rows).map(move |i| (i, j))
+        });
+
+        for (line, tokens) in records {
+            for (column, token) in tokens {
+                let (i, j) = positions.next().ok_or_else(|| {
+                    parse_error(
+                        line,
+                        column,
+                        "more values than the matrix holds".to_string(),
+                    )
+                })?;
+                let value = parse_value(token, line, column)?;
+
+                entries.push((i, j, value));
+            }
+        }
+
+        if positions.next().is_some() {
+            return Err(parse_error(
+                input.lines().count().max(1),
+                1,
+                "fewer values than the matrix holds".to_string(),
+            ));
+        }
+    }
+
+    // A sparse coordinate file can still describe a huge dense matrix, so
+    // report a failed allocation instead of aborting.
+    let mut data = vec![];
+    data.try_reserve_exact(len).map_err(|_| {
+        parse_error(
+            size_line,
+            size[0].0,
+            format!("a {}x{} matrix does not fit in memory", rows, cols),
+        )
+    })?;
+    data.resize(len, T::zero());
+    let mut mat = Matrix::new(rows, cols, data).expect("data holds `rows * cols` elements");
+
+    for (i, j, value) in entries {
+        mat[(i, j)] = value;
+        symmetry.mirror(&mut mat, i, j, value);
+    }
+
+    Ok(mat)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Symmetry {
+    General,
+    Symmetric,
+    SkewSymmetric,
+}
+
+impl Symmetry {
+    fn name(self) -> &'static str {
+        match self {
+            Symmetry::General => "general",
+            Symmetry::Symmetric => "symmetric",
+            Symmetry::SkewSymmetric => "skew-symmetric",
+        }
+    }
+
+    /// Returns whether files with this symmetry may store the entry at
+    /// (`i`, `j`): symmetric ones only hold the lower triangle, without the
+    /// diagonal when skew-symmetric.
+    fn stores(self, i: usize, j: usize) -> bool {
+        match self {
+            Symmetry::General => true,
+            Symmetry::Symmetric => i >= j,
+            Symmetry::SkewSymmetric => i > j,
+        }
+    }
+
+    /// Fills in the entry implied by `value` being stored at (`i`, `j`).
+    fn mirror<T: Numeric>(self, mat: &mut Matrix<T>, i: usize, j: usize, value: T) {
+        if i == j {
+            return;
+        }
+
+        match self {
+            Symmetry::General => (),
+            Symmetry::Symmetric => mat[(j, i)] = value,
+            Symmetry::SkewSymmetric => mat[(j, i)] = T::zero() - value,
+        }
+    }
+}
+
+/// Reads a matrix in Matrix Market format, see `parse_matrix_market`.
+pub fn read_matrix_market<T: Element, R: Read>(mut reader: R) -> Result<Matrix<T>, MatrixIoError> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    parse_matrix_market(&input)
+}
+
+/// Writes `mat` in Matrix Market format, as a `general` matrix.
+pub fn write_matrix_market<T: Element, W: Write>(
+    mat: &Matrix<T>,
+    format: MtxFormat,
+    mut writer: W,
+) -> io::Result<()> {
+    let (rows, cols) = mat.shape();
+
+    match format {
+        MtxFormat::Coordinate => {
+            let entries: Vec<(usize, usize, T)> = (0..rows)
+                .flat_map(|i| (0..cols).map(move |j| (i, j)))
+                .map(|(i, j)| (i, j, mat[(i, j)]))
+                .filter(|&(_, _, x)| x != T::zero())
+                .collect();
+
+            writeln!(
+                writer,
+                "%%MatrixMarket matrix coordinate {} general",
+                T::FIELD
+            )?;
+            writeln!(writer, "{} {} {}", rows, cols, entries.len())?;
+            for (i, j, x) in entries {
+                writeln!(writer, "{} {} {}", i + 1, j + 1, x)?;
+            }
+        }
+        MtxFormat::Array => {
+            writeln!(writer, "%%MatrixMarket matrix array {} general", T::FIELD)?;
+            writeln!(writer, "{} {}", rows, cols)?;
+            for j in 0..cols {
+                for i in 0..rows {
+                    writeln!(writer, "{}", mat[(i, j)])?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
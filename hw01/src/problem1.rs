@@ -1,39 +1,98 @@
 // problem1.rs
 
-/// Computes the sum of all elements in the input i32 slice named `slice`
-pub fn sum(slice: &[i32]) -> i32 {
-    let mut count: i32 = 0;
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hash;
+use std::iter::Sum;
 
-    for i in slice {
-        count = count + i;
-    }
+/// Integer types that support overflow-aware addition.
+pub trait CheckedAdd: Copy {
+    /// The additive identity.
+    fn zero() -> Self;
+    /// Adds `other`, returning `None` on overflow.
+    fn checked_add(self, other: Self) -> Option<Self>;
+    /// Adds `other`, clamping at the bounds of the type.
+    fn saturating_add(self, other: Self) -> Self;
+}
+
+macro_rules! impl_checked_add {
+    ($($t:ty),*) => {
+        $(
+            impl CheckedAdd for $t {
+                fn zero() -> $t {
+                    0
+                }
+
+                fn checked_add(self, other: $t) -> Option<$t> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn saturating_add(self, other: $t) -> $t {
+                    <$t>::saturating_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_add!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Computes the sum of all elements in the input slice named `slice`
+pub fn sum<T: Copy + Sum>(slice: &[T]) -> T {
+    slice.iter().cloned().sum()
+}
 
-    count
+/// Computes the sum of all elements in `slice`, or `None` if it overflows.
+pub fn checked_sum<T: CheckedAdd>(slice: &[T]) -> Option<T> {
+    slice
+        .iter()
+        .try_fold(T::zero(), |count, &i| count.checked_add(i))
 }
 
-/// Deduplicates items in the input vector `vs`. Produces a vector containing
+/// Computes the sum of all elements in `slice`, clamping at the bounds of the
+/// type instead of overflowing.
+///
+/// Every partial sum is clamped, so the result is inexact whenever one of
+/// them saturated, even if later elements bring the total back in range:
+/// `[i32::MAX, 1, -1]` sums to `i32::MAX - 1`.
+pub fn saturating_sum<T: CheckedAdd>(slice: &[T]) -> T {
+    slice
+        .iter()
+        .fold(T::zero(), |count, &i| count.saturating_add(i))
+}
+
+/// Deduplicates items in the input slice `vs`. Produces a vector containing
 /// the first instance of each distinct element of `vs`, preserving the
 /// original order.
-pub fn dedup(vs: &Vec<i32>) -> Vec<i32> {
-    let mut new_vs: Vec<i32> = vec![];
+pub fn dedup<T: Hash + Eq + Clone>(vs: &[T]) -> Vec<T> {
+    let mut seen: HashSet<&T> = HashSet::with_capacity(vs.len());
 
-    for i in vs {
-        if !new_vs.contains(i) {
-            new_vs.push(*i);
-        }
-    }
+    vs.iter().filter(|&i| seen.insert(i)).cloned().collect()
+}
 
-    new_vs
+/// Like `dedup`, for types that can be ordered but not hashed.
+pub fn dedup_ord<T: Ord + Clone>(vs: &[T]) -> Vec<T> {
+    let mut seen: BTreeSet<&T> = BTreeSet::new();
+
+    vs.iter().filter(|&i| seen.insert(i)).cloned().collect()
 }
 
-/// Filters a vector `vs` using a predicate `pred` (a function from `i32` to
+/// Filters a slice `vs` using a predicate `pred` (a function from `T` to
 /// `bool`). Returns a new vector containing only elements that satisfy `pred`.
-pub fn filter(vs: &Vec<i32>, pred: &dyn Fn(i32) -> bool) -> Vec<i32> {
-    let mut new_vs: Vec<i32> = vec![];
+///
+/// `pred` takes its argument by value so that existing callers passing a
+/// `&dyn Fn(i32) -> bool` keep working. This costs a clone of every element,
+/// plus one of each kept element: for types that are expensive to clone,
+/// prefer `vs.iter().filter(..).cloned()`.
+pub fn filter<T, P>(vs: &[T], pred: P) -> Vec<T>
+where
+    T: Clone,
+    P: Fn(T) -> bool,
+{
+    let mut new_vs: Vec<T> = vec![];
 
     for i in vs {
-        if pred(*i) {
-            new_vs.push(*i);
+        if pred(i.clone()) {
+            new_vs.push(i.clone());
         }
     }
 
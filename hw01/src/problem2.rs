@@ -1,27 +1,444 @@
+use std::error;
+use std::fmt;
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
+use std::thread;
+
+/// Element types that can be stored in a `Matrix`.
+pub trait Numeric:
+    Copy
+    + PartialEq
+    + fmt::Debug
+    + Send
+    + Sync
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+{
+    /// The additive identity.
+    fn zero() -> Self;
+    /// The multiplicative identity.
+    fn one() -> Self;
+}
+
+macro_rules! impl_numeric {
+    ($($t:ty => $zero:expr, $one:expr;)*) => {
+        $(
+            impl Numeric for $t {
+                fn zero() -> $t {
+                    $zero
+                }
+
+                fn one() -> $t {
+                    $one
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric! {
+    f32 => 0.0, 1.0;
+    f64 => 0.0, 1.0;
+    i32 => 0, 1;
+    i64 => 0, 1;
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+/// An error produced when building or combining matrices.
+pub enum MatrixError {
+    /// The backing data does not hold exactly `rows * cols` elements.
+    DataLength { expected: usize, actual: usize },
+    /// A row does not have the same length as the first row.
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// The shapes of the operands are incompatible for the operation.
+    DimensionMismatch {
+        left: (usize, usize),
+        right: (usize, usize),
+    },
+    /// An index lies outside a matrix of the given shape.
+    OutOfBounds {
+        index: (usize, usize),
+        shape: (usize, usize),
+    },
+    /// The number of elements `rows * cols` does not fit in a `usize`.
+    TooLarge { rows: usize, cols: usize },
+}
+
+/// Side length of the square tiles used by the blocked kernel.
+pub const BLOCK_SIZE: usize = 64;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+/// The kernel used to compute a matrix product.
+pub enum MultStrategy {
+    /// The straightforward triple loop, one dot product per output element.
+    Naive,
+    /// Works on `BLOCK_SIZE` x `BLOCK_SIZE` tiles so operands stay in cache.
+    #[default]
+    Blocked,
+    /// Runs the blocked kernel with the output rows split across `threads`
+    /// threads. Zero uses `std::thread::available_parallelism`.
+    Parallel { threads: usize },
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MatrixError::DataLength { expected, actual } => write!(
+                f,
+                "expected {} elements of matrix data, found {}",
+                expected, actual
+            ),
+            MatrixError::RaggedRow {
+                row,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "row {} has {} columns, expected {}",
+                row, actual, expected
+            ),
+            MatrixError::DimensionMismatch { left, right } => write!(
+                f,
+                "incompatible matrix dimensions {}x{} and {}x{}",
+                left.0, left.1, right.0, right.1
+            ),
+            MatrixError::OutOfBounds { index, shape } => write!(
+                f,
+                "index ({}, {}) is out of bounds for a {}x{} matrix",
+                index.0, index.1, shape.0, shape.1
+            ),
+            MatrixError::TooLarge { rows, cols } => {
+                write!(f, "a {}x{} matrix has too many elements", rows, cols)
+            }
+        }
+    }
+}
+
+impl error::Error for MatrixError {}
+
 /// Represents a matrix in row-major order
-pub type Matrix = Vec<Vec<f32>>;
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Numeric> Matrix<T> {
+    /// Creates a `rows` x `cols` matrix from its elements in row-major order.
+    pub fn new(rows: usize, cols: usize, data: Vec<T>) -> Result<Matrix<T>, MatrixError> {
+        let len = rows
+            .checked_mul(cols)
+            .ok_or(MatrixError::TooLarge { rows, cols })?;
+
+        if data.len() != len {
+            return Err(MatrixError::DataLength {
+                expected: len,
+                actual: data.len(),
+            });
+        }
+
+        Ok(Matrix { rows, cols, data })
+    }
+
+    /// Creates a `rows` x `cols` matrix filled with zeros.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows * cols` overflows a `usize`.
+    pub fn zeros(rows: usize, cols: usize) -> Matrix<T> {
+        let len = rows
+            .checked_mul(cols)
+            .unwrap_or_else(|| panic!("a {}x{} matrix has too many elements", rows, cols));
+
+        Matrix {
+            rows,
+            cols,
+            data: vec![T::zero(); len],
+        }
+    }
+
+    /// Creates the `n` x `n` identity matrix.
+    pub fn identity(n: usize) -> Matrix<T> {
+        let mut mat = Matrix::zeros(n, n);
+
+        for i in 0..n {
+            mat[(i, i)] = T::one();
+        }
+
+        mat
+    }
+
+    /// Creates a matrix from a slice of rows, which must all have the same
+    /// length.
+    pub fn from_rows(rows: &[Vec<T>]) -> Result<Matrix<T>, MatrixError> {
+        let cols = rows.first().map_or(0, |row| row.len());
+        let mut data = Vec::with_capacity(rows.len() * cols);
+
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != cols {
+                return Err(MatrixError::RaggedRow {
+                    row: i,
+                    expected: cols,
+                    actual: row.len(),
+                });
+            }
+            data.extend_from_slice(row);
+        }
+
+        Ok(Matrix {
+            rows: rows.len(),
+            cols,
+            data,
+        })
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the dimensions as `(rows, cols)`.
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns the element at (`row`, `col`), or `None` if out of bounds.
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.rows && col < self.cols {
+            self.data.get(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the elements of row `i`.
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// Returns all elements in row-major order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Consumes the matrix, returning its elements in row-major order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
 
-/// Computes the product of the inputs `mat1` and `mat2`.
-pub fn mat_mult(mat1: &Matrix, mat2: &Matrix) -> Matrix {
-    let mut mat_result: Matrix = vec![];
+    /// Copies the matrix into a vector of rows.
+    pub fn to_rows(&self) -> Vec<Vec<T>> {
+        (0..self.rows).map(|i| self.row(i).to_vec()).collect()
+    }
+
+    /// Computes the product of `self` and `other` with the default strategy.
+    pub fn mat_mult(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        self.mat_mult_with(other, MultStrategy::default())
+    }
+
+    /// Computes the product of `self` and `other` using `strategy`.
+    ///
+    /// Every strategy accumulates each output element in the same order, so
+    /// they all produce exactly the same result.
+    pub fn mat_mult_with(
+        &self,
+        other: &Matrix<T>,
+        strategy: MultStrategy,
+    ) -> Result<Matrix<T>, MatrixError> {
+        if self.cols != other.rows {
+            return Err(self.mismatch(other));
+        }
+
+        let mut result = Matrix::zeros(self.rows, other.cols);
+
+        if result.data.is_empty() || self.cols == 0 {
+            return Ok(result);
+        }
+
+        match strategy {
+            MultStrategy::Naive => {
+                naive_kernel(&self.data, &other.data, &mut result.data, self.cols)
+            }
+            MultStrategy::Blocked => {
+                blocked_kernel(&self.data, &other.data, &mut result.data, self.cols)
+            }
+            MultStrategy::Parallel { threads } => {
+                let threads = if threads == 0 {
+                    thread::available_parallelism().map_or(1, |n| n.get())
+                } else {
+                    threads
+                };
+                let rows_per_thread = self.rows.div_ceil(threads);
+                let (inner, cols) = (self.cols, other.cols);
+
+                thread::scope(|scope| {
+                    for (lhs, out) in self
+                        .data
+                        .chunks(rows_per_thread * inner)
+                        .zip(result.data.chunks_mut(rows_per_thread * cols))
+                    {
+                        let rhs = &other.data;
+                        scope.spawn(move || blocked_kernel(lhs, rhs, out, inner));
+                    }
+                });
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Computes the element-wise sum of `self` and `other`.
+    pub fn checked_add(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        self.zip_with(other, |a, b| a + b)
+    }
+
+    /// Computes the element-wise difference of `self` and `other`.
+    pub fn checked_sub(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        self.zip_with(other, |a, b| a - b)
+    }
+
+    fn zip_with<F>(&self, other: &Matrix<T>, f: F) -> Result<Matrix<T>, MatrixError>
+    where
+        F: Fn(T, T) -> T,
+    {
+        if self.shape() != other.shape() {
+            return Err(self.mismatch(other));
+        }
+
+        Ok(Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(&other.data)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+        })
+    }
+
+    fn mismatch(&self, other: &Matrix<T>) -> MatrixError {
+        MatrixError::DimensionMismatch {
+            left: self.shape(),
+            right: other.shape(),
+        }
+    }
+}
 
-    let size_mat1 = (mat1.len() as i32, mat1[0].len() as i32);
-    let size_mat2 = (mat2.len() as i32, mat2[0].len() as i32);
+impl<T: Numeric> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
 
-    for i in 0..size_mat1.0 {
-        let mut new_vec: Vec<f32> = vec![];
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "matrix index out of bounds"
+        );
+        &self.data[row * self.cols + col]
+    }
+}
+
+impl<T: Numeric> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "matrix index out of bounds"
+        );
+        &mut self.data[row * self.cols + col]
+    }
+}
+
+// The kernels compute `out = lhs * rhs`, where `lhs` holds whole rows of
+// length `inner` and `out` holds the matching output rows. `out` must start
+// zeroed, and neither `inner` nor the output width may be zero.
+
+fn naive_kernel<T: Numeric>(lhs: &[T], rhs: &[T], out: &mut [T], inner: usize) {
+    let cols = rhs.len() / inner;
 
-        for j in 0..size_mat2.1 {
-            let mut x: f32 = 0.0;
+    for (i, out_row) in out.chunks_mut(cols).enumerate() {
+        let lhs_row = &lhs[i * inner..(i + 1) * inner];
 
-            for k in 0..size_mat1.1 {
-                x = x + mat1[i as usize][k as usize] * mat2[k as usize][j as usize];
+        for (j, x) in out_row.iter_mut().enumerate() {
+            for (k, &a) in lhs_row.iter().enumerate() {
+                *x = *x + a * rhs[k * cols + j];
             }
+        }
+    }
+}
+
+fn blocked_kernel<T: Numeric>(lhs: &[T], rhs: &[T], out: &mut [T], inner: usize) {
+    let cols = rhs.len() / inner;
+    let rows = out.len() / cols;
+
+    for ii in (0..rows).step_by(BLOCK_SIZE) {
+        let i_end = (ii + BLOCK_SIZE).min(rows);
+
+        for kk in (0..inner).step_by(BLOCK_SIZE) {
+            let k_end = (kk + BLOCK_SIZE).min(inner);
+
+            for jj in (0..cols).step_by(BLOCK_SIZE) {
+                let j_end = (jj + BLOCK_SIZE).min(cols);
 
-            new_vec.push(x);
+                for i in ii..i_end {
+                    let out_row = &mut out[i * cols + jj..i * cols + j_end];
+
+                    for k in kk..k_end {
+                        let a = lhs[i * inner + k];
+                        let rhs_row = &rhs[k * cols + jj..k * cols + j_end];
+
+                        for (x, &b) in out_row.iter_mut().zip(rhs_row) {
+                            *x = *x + a * b;
+                        }
+                    }
+                }
+            }
         }
-        mat_result.push(new_vec);
     }
+}
+
+// The operators panic on incompatible shapes, like slice indexing does. Use
+// `checked_add`, `checked_sub` or `mat_mult` to get a `MatrixError` instead.
+macro_rules! impl_matrix_op {
+    ($op:ident, $method:ident, $checked:ident) => {
+        impl<'a, T: Numeric> $op<&'a Matrix<T>> for &'a Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, other: &'a Matrix<T>) -> Matrix<T> {
+                self.$checked(other).unwrap_or_else(|err| panic!("{}", err))
+            }
+        }
+
+        impl<T: Numeric> $op for Matrix<T> {
+            type Output = Matrix<T>;
+
+            fn $method(self, other: Matrix<T>) -> Matrix<T> {
+                (&self).$method(&other)
+            }
+        }
+    };
+}
+
+impl_matrix_op!(Add, add, checked_add);
+impl_matrix_op!(Sub, sub, checked_sub);
+impl_matrix_op!(Mul, mul, mat_mult);
 
-    mat_result
+/// Computes the product of the inputs `mat1` and `mat2`, given as vectors of
+/// rows.
+///
+/// This is a compatibility wrapper around `Matrix::mat_mult`. It panics if an
+/// input has rows of different lengths or the dimensions do not agree.
+pub fn mat_mult(mat1: &[Vec<f32>], mat2: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    Matrix::from_rows(mat1)
+        .and_then(|mat1| Matrix::from_rows(mat2).and_then(|mat2| mat1.mat_mult(&mat2)))
+        .map(|result| result.to_rows())
+        .unwrap_or_else(|err| panic!("{}", err))
 }
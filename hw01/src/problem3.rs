@@ -1,22 +1,433 @@
+/// Number of odd candidates examined per segment by `primes_in_range`,
+/// which bounds its working memory to `SEGMENT_BITS / 8` bytes.
+pub const SEGMENT_BITS: usize = 1 << 18;
+
+/// A packed bit set, one bit per odd number.
+struct OddBits {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl OddBits {
+    fn new(len: usize) -> OddBits {
+        OddBits {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    fn clear(&mut self) {
+        for word in &mut self.words {
+            *word = 0;
+        }
+    }
+
+    fn set(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    fn get(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    /// Returns the first unset index at or after `from`, if any.
+    fn next_unset(&self, from: usize) -> Option<usize> {
+        let mut w = from / 64;
+
+        if w >= self.words.len() {
+            return None;
+        }
+
+        let mut word = !self.words[w] & (!0 << (from % 64));
+
+        loop {
+            if word != 0 {
+                let i = w * 64 + word.trailing_zeros() as usize;
+                return if i < self.len { Some(i) } else { None };
+            }
+
+            w += 1;
+            if w == self.words.len() {
+                return None;
+            }
+            word = !self.words[w];
+        }
+    }
+}
+
 /// Find all prime numbers less than `n`.
 /// For example, `sieve(7)` should return `[2, 3, 5]`
 pub fn sieve(n: u32) -> Vec<u32> {
     let mut primes: Vec<u32> = vec![];
 
-    for i in 2..n {
-        let mut is_prime = true;
+    if n <= 2 {
+        return primes;
+    }
+    primes.push(2);
+
+    // Bit `i` stands for the odd number `2 * i + 1`, starting with 1 itself.
+    let n = n as usize;
+    let mut composite = OddBits::new(n / 2);
+    composite.set(0);
+
+    let mut i = 1;
+    while (2 * i + 1) * (2 * i + 1) < n {
+        if !composite.get(i) {
+            let p = 2 * i + 1;
+            let mut j = p * p / 2;
+
+            while j < composite.len {
+                composite.set(j);
+                j += p;
+            }
+        }
+        i += 1;
+    }
+
+    let mut next = composite.next_unset(0);
+    while let Some(i) = next {
+        primes.push((2 * i + 1) as u32);
+        next = composite.next_unset(i + 1);
+    }
+
+    primes
+}
+
+/// Enumerates the primes in `[lo, hi)` in increasing order.
+///
+/// The range is sieved one segment of `SEGMENT_BITS` odd numbers at a time,
+/// so memory use depends on `sqrt(hi)` and not on the width of the range.
+pub fn primes_in_range(lo: u64, hi: u64) -> SegmentedSieve {
+    SegmentedSieve::new(lo, hi)
+}
+
+/// An iterator over the primes in a range, see `primes_in_range`.
+pub struct SegmentedSieve {
+    hi: u64,
+    /// The odd number represented by bit 0 of `composite`.
+    start: u64,
+    /// Index of the next bit of `composite` to look at.
+    pos: usize,
+    composite: OddBits,
+    /// All odd primes below `base_limit`.
+    base: Vec<u64>,
+    base_limit: u64,
+    yield_two: bool,
+}
+
+impl SegmentedSieve {
+    fn new(lo: u64, hi: u64) -> SegmentedSieve {
+        let mut sieve = SegmentedSieve {
+            hi,
+            start: lo.max(1) | 1,
+            pos: 0,
+            composite: OddBits::new(SEGMENT_BITS),
+            base: vec![],
+            base_limit: 2,
+            yield_two: lo <= 2 && 2 < hi,
+        };
+
+        sieve.sieve_segment();
+        sieve
+    }
+
+    /// Returns one past the last number covered by the current segment.
+    fn segment_end(&self) -> u64 {
+        self.start
+            .saturating_add(2 * SEGMENT_BITS as u64)
+            .min(self.hi)
+    }
+
+    /// Makes sure `base` holds every prime whose square is below `end`.
+    fn extend_base(&mut self, end: u64) {
+        let needed = end.isqrt() + 1;
+
+        if needed <= self.base_limit || self.base_limit >= u32::MAX as u64 {
+            return;
+        }
+
+        let limit = needed.max(2 * self.base_limit).min(u32::MAX as u64);
+        self.base = sieve(limit as u32)
+            .into_iter()
+            .skip(1)
+            .map(|p| p as u64)
+            .collect();
+        self.base_limit = limit;
+    }
+
+    fn sieve_segment(&mut self) {
+        self.composite.clear();
+        self.pos = 0;
+
+        let end = self.segment_end();
+        if self.start >= end {
+            return;
+        }
+
+        self.extend_base(end);
+
+        let width = (end - self.start).div_ceil(2) as usize;
+        self.composite.len = width;
+        if self.start == 1 {
+            self.composite.set(0);
+        }
 
-        for j in 0..primes.len() {
-            if i as u32 % primes[j] as u32 == 0 {
-                is_prime = false;
+        for &p in &self.base {
+            let square = p * p;
+            if square >= end {
                 break;
             }
+
+            // The first odd multiple of `p` inside the segment, but never `p`
+            // itself.
+            let mut m = if square >= self.start {
+                square
+            } else {
+                match self.start.div_ceil(p).checked_mul(p) {
+                    Some(m) => m,
+                    None => continue,
+                }
+            };
+            if m.is_multiple_of(2) {
+                m = match m.checked_add(p) {
+                    Some(m) => m,
+                    None => continue,
+                };
+            }
+
+            let mut j = ((m - self.start) / 2) as usize;
+            while j < width {
+                self.composite.set(j);
+                j += p as usize;
+            }
+        }
+    }
+}
+
+impl Iterator for SegmentedSieve {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.yield_two {
+            self.yield_two = false;
+            return Some(2);
         }
 
-        if is_prime {
-            primes.push(i)
+        loop {
+            if self.start >= self.hi {
+                return None;
+            }
+
+            if let Some(i) = self.composite.next_unset(self.pos) {
+                self.pos = i + 1;
+                return Some(self.start + 2 * i as u64);
+            }
+
+            let end = self.segment_end();
+            if end >= self.hi {
+                self.start = self.hi;
+                return None;
+            }
+            self.start = end | 1;
+            self.sieve_segment();
         }
     }
+}
 
-    primes
+/// An unbounded iterator over all primes in increasing order.
+///
+/// Primes are produced segment by segment, extending the base primes used
+/// for sieving as the iterator advances.
+pub struct Primes {
+    segments: SegmentedSieve,
+}
+
+impl Primes {
+    /// Creates an iterator starting at 2.
+    pub fn new() -> Primes {
+        Primes {
+            segments: SegmentedSieve::new(0, u64::MAX),
+        }
+    }
+}
+
+impl Default for Primes {
+    fn default() -> Primes {
+        Primes::new()
+    }
+}
+
+impl Iterator for Primes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.segments.next()
+    }
+}
+
+/// Returns the `n`-th prime counting from zero, so `nth_prime(0)` is 2.
+pub fn nth_prime(n: usize) -> u64 {
+    Primes::new()
+        .nth(n)
+        .expect("there are infinitely many primes")
+}
+
+/// Returns the number of primes less than or equal to `n`.
+pub fn prime_count(n: u64) -> usize {
+    let mut count = primes_in_range(0, n).count();
+
+    if is_prime(n) {
+        count += 1;
+    }
+
+    count
+}
+
+/// Primes below this bound are divided out by trial division in `factorize`.
+const TRIAL_DIVISION_LIMIT: u32 = 1000;
+
+/// Witnesses that make Miller–Rabin deterministic for every `u64`.
+const WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    let mut result = 1;
+    base %= m;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+
+    result
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+
+    a
+}
+
+/// Tests whether `n` is prime using a deterministic Miller–Rabin test.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+
+    for &p in WITNESSES.iter() {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    WITNESSES.iter().all(|&a| {
+        let mut x = pow_mod(a, d, n);
+
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+
+        false
+    })
+}
+
+/// Finds a non-trivial factor of the odd composite `n` with Pollard's rho
+/// method, using Brent's cycle detection.
+fn pollard_rho(n: u64) -> u64 {
+    for c in 1.. {
+        let f = |x: u64| ((x as u128 * x as u128 + c as u128) % n as u128) as u64;
+        let (mut x, mut y) = (2, 2);
+        let mut d = 1;
+        let mut power = 1;
+        let mut lam = 0;
+
+        while d == 1 {
+            if power == lam {
+                x = y;
+                power *= 2;
+                lam = 0;
+            }
+            y = f(y);
+            lam += 1;
+            d = gcd(x.abs_diff(y), n);
+        }
+
+        if d != n {
+            return d;
+        }
+    }
+
+    unreachable!()
+}
+
+fn collect_factors(n: u64, factors: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+
+    if is_prime(n) {
+        factors.push(n);
+        return;
+    }
+
+    let d = pollard_rho(n);
+    collect_factors(d, factors);
+    collect_factors(n / d, factors);
+}
+
+/// Computes the prime factorization of `n` as `(prime, exponent)` pairs in
+/// increasing order of prime. Both 0 and 1 have an empty factorization.
+pub fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut result: Vec<(u64, u32)> = vec![];
+
+    if n == 0 {
+        return result;
+    }
+
+    for p in sieve(TRIAL_DIVISION_LIMIT) {
+        let p = p as u64;
+        let mut exp = 0;
+
+        while n.is_multiple_of(p) {
+            n /= p;
+            exp += 1;
+        }
+
+        if exp > 0 {
+            result.push((p, exp));
+        }
+    }
+
+    let mut factors = vec![];
+    collect_factors(n, &mut factors);
+    factors.sort_unstable();
+
+    for p in factors {
+        match result.last_mut() {
+            Some((q, exp)) if *q == p => *exp += 1,
+            _ => result.push((p, 1)),
+        }
+    }
+
+    result
 }
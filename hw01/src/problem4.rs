@@ -1,7 +1,9 @@
+use std::convert::TryFrom;
+
 /// #[derive(...)] statements define certain properties on the enum for you for
 /// free (printing, equality testing, the ability to copy values). More on this
 /// when we cover Enums in detail.
-
+///
 /// You can use any of the variants of the `Peg` enum by writing `aux`, etc.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Peg {
@@ -35,3 +37,309 @@ pub fn hanoi(num_discs: u32, src: Peg, aux: Peg, dst: Peg) -> Vec<Move> {
 
     moves
 }
+
+/// Lazily generates the same moves as `hanoi`, without recursion or
+/// allocation. Supports up to 64 discs.
+///
+/// Move `m` (counting from 1) moves disc `m.trailing_zeros()`, and its pegs
+/// follow from the binary representation of `m`, so `nth` jumps straight to
+/// any move.
+pub fn hanoi_iter(num_discs: u32, src: Peg, aux: Peg, dst: Peg) -> HanoiMoves {
+    assert!(num_discs <= 64, "at most 64 discs are supported");
+
+    // The formula cycles through the pegs in the order src, dst, aux when
+    // the number of discs is odd, and src, aux, dst when it is even.
+    let pegs = if num_discs % 2 == 1 {
+        [src, aux, dst]
+    } else {
+        [src, dst, aux]
+    };
+
+    HanoiMoves {
+        pegs,
+        next: 0,
+        total: if num_discs == 64 {
+            u64::MAX
+        } else {
+            (1 << num_discs) - 1
+        },
+    }
+}
+
+/// An iterator over the moves of a Tower of Hanoi solution, see `hanoi_iter`.
+#[derive(Clone, Debug)]
+pub struct HanoiMoves {
+    pegs: [Peg; 3],
+    /// Number of moves already produced.
+    next: u64,
+    total: u64,
+}
+
+impl HanoiMoves {
+    /// Returns the total number of moves, `2^n - 1`.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Computes move number `m`, counting from 1.
+    fn move_at(&self, m: u64) -> Move {
+        let src = (m & (m - 1)) % 3;
+        let dst = ((m | (m - 1)) as u128 + 1) % 3;
+
+        (self.pegs[src as usize], self.pegs[dst as usize])
+    }
+}
+
+impl Iterator for HanoiMoves {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        if self.next == self.total {
+            return None;
+        }
+
+        self.next += 1;
+        Some(self.move_at(self.next))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Move> {
+        let remaining = self.total - self.next;
+
+        if n as u64 >= remaining {
+            self.next = self.total;
+            return None;
+        }
+
+        self.next += n as u64 + 1;
+        Some(self.move_at(self.next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.total - self.next;
+
+        match usize::try_from(remaining) {
+            Ok(n) => (n, Some(n)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+/// Something that names a peg: either a `Peg` or a zero-based peg index.
+pub trait PegIndex: Copy {
+    /// Returns the zero-based index of the peg.
+    fn index(self) -> usize;
+}
+
+impl PegIndex for Peg {
+    fn index(self) -> usize {
+        match self {
+            Peg::A => 0,
+            Peg::B => 1,
+            Peg::C => 2,
+        }
+    }
+}
+
+impl PegIndex for usize {
+    fn index(self) -> usize {
+        self
+    }
+}
+
+/// A move between two pegs given by index: (source, destination).
+pub type IndexMove = (usize, usize);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// The reason a move cannot be applied to a `HanoiState`.
+pub enum MoveError {
+    /// The peg does not exist.
+    NoSuchPeg(usize),
+    /// The source peg holds no discs.
+    EmptyPeg(usize),
+    /// The moved disc is larger than the top disc of the destination.
+    LargerOnSmaller { disc: u32, onto: u32 },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// The reason a sequence of moves does not solve a puzzle.
+pub enum VerifyError {
+    /// The source or target peg does not exist.
+    NoSuchPeg(usize),
+    /// The move at position `index` is illegal.
+    IllegalMove { index: usize, error: MoveError },
+    /// Every move is legal but the discs do not all end on the target peg.
+    Unsolved,
+}
+
+/// The discs on each peg of a Tower of Hanoi puzzle.
+///
+/// Discs are numbered from 1 (the smallest) and each peg lists its discs from
+/// bottom to top.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HanoiState {
+    pegs: Vec<Vec<u32>>,
+}
+
+impl HanoiState {
+    /// Creates a puzzle with `num_discs` discs stacked on peg `src` out of
+    /// `num_pegs` pegs. Panics if `src` is not one of the pegs.
+    pub fn new<P: PegIndex>(num_discs: u32, num_pegs: usize, src: P) -> HanoiState {
+        let src = src.index();
+        assert!(src < num_pegs, "source peg {} does not exist", src);
+
+        let mut pegs = vec![vec![]; num_pegs];
+        pegs[src] = (1..=num_discs).rev().collect();
+
+        HanoiState { pegs }
+    }
+
+    /// Returns the discs on each peg, from bottom to top.
+    pub fn pegs(&self) -> &[Vec<u32>] {
+        &self.pegs
+    }
+
+    /// Moves the top disc of `mv.0` onto `mv.1`, unless that is illegal.
+    pub fn apply<P: PegIndex>(&mut self, mv: (P, P)) -> Result<(), MoveError> {
+        let (src, dst) = (mv.0.index(), mv.1.index());
+
+        for &peg in [src, dst].iter() {
+            if peg >= self.pegs.len() {
+                return Err(MoveError::NoSuchPeg(peg));
+            }
+        }
+
+        let disc = *self.pegs[src].last().ok_or(MoveError::EmptyPeg(src))?;
+
+        if let Some(&onto) = self.pegs[dst].last() {
+            if onto < disc {
+                return Err(MoveError::LargerOnSmaller { disc, onto });
+            }
+        }
+
+        self.pegs[src].pop();
+        self.pegs[dst].push(disc);
+
+        Ok(())
+    }
+
+    /// Returns whether every disc is stacked on peg `dst`.
+    pub fn is_solved<P: PegIndex>(&self, dst: P) -> bool {
+        let dst = dst.index();
+
+        self.pegs
+            .iter()
+            .enumerate()
+            .all(|(i, peg)| i == dst || peg.is_empty())
+    }
+}
+
+/// Replays `moves` on a puzzle with `num_discs` discs on peg `src` out of
+/// `num_pegs` pegs, and checks that they legally move every disc to `dst`.
+pub fn verify<P: PegIndex>(
+    num_discs: u32,
+    num_pegs: usize,
+    src: P,
+    dst: P,
+    moves: &[(P, P)],
+) -> Result<(), VerifyError> {
+    for &peg in [src.index(), dst.index()].iter() {
+        if peg >= num_pegs {
+            return Err(VerifyError::NoSuchPeg(peg));
+        }
+    }
+
+    let mut state = HanoiState::new(num_discs, num_pegs, src);
+
+    for (index, &mv) in moves.iter().enumerate() {
+        state
+            .apply(mv)
+            .map_err(|error| VerifyError::IllegalMove { index, error })?;
+    }
+
+    if state.is_solved(dst) {
+        Ok(())
+    } else {
+        Err(VerifyError::Unsolved)
+    }
+}
+
+/// `splits[k][n]` is the number of discs to park on an intermediate peg when
+/// moving `n` discs with `k` pegs, chosen by the Frame–Stewart recurrence.
+fn frame_stewart_splits(num_discs: usize, num_pegs: usize) -> Vec<Vec<usize>> {
+    let mut costs = vec![vec![0u64; num_discs + 1]; num_pegs + 1];
+    let mut splits = vec![vec![0; num_discs + 1]; num_pegs + 1];
+
+    for n in 1..=num_discs {
+        costs[3][n] = costs[3][n - 1].saturating_mul(2).saturating_add(1);
+        splits[3][n] = n - 1;
+    }
+
+    for k in 4..=num_pegs {
+        for n in 1..=num_discs {
+            let (cost, t) = (1..n)
+                .map(|t| {
+                    let cost = costs[k][t]
+                        .saturating_mul(2)
+                        .saturating_add(costs[k - 1][n - t]);
+                    (cost, t)
+                })
+                .min()
+                .unwrap_or((1, 0));
+
+            costs[k][n] = cost;
+            splits[k][n] = t;
+        }
+    }
+
+    splits
+}
+
+fn mv_k(
+    num_discs: usize,
+    moves: &mut Vec<IndexMove>,
+    src: usize,
+    dst: usize,
+    spare: &[usize],
+    splits: &[Vec<usize>],
+) {
+    if num_discs == 0 {
+        return;
+    }
+    if num_discs == 1 {
+        moves.push((src, dst));
+        return;
+    }
+
+    // Park the top `t` discs on `aux` using every peg, move the rest with
+    // the pegs that are left, then bring the parked discs back on top.
+    let t = splits[spare.len() + 2][num_discs];
+    let aux = spare[0];
+    let rest = &spare[1..];
+    let with = |peg: usize| rest.iter().cloned().chain(Some(peg)).collect::<Vec<_>>();
+
+    mv_k(t, moves, src, aux, &with(dst), splits);
+    mv_k(num_discs - t, moves, src, dst, rest, splits);
+    mv_k(t, moves, aux, dst, &with(src), splits);
+}
+
+/// Solves for a sequence of moves taking `num_discs` discs from peg `src` to
+/// peg `dst` using `num_pegs` pegs, following the Frame–Stewart algorithm.
+///
+/// With three pegs this produces the same moves as `hanoi`. Panics if there
+/// are fewer than three pegs or `src` and `dst` are not distinct pegs.
+pub fn hanoi_k(num_discs: u32, num_pegs: usize, src: usize, dst: usize) -> Vec<IndexMove> {
+    assert!(num_pegs >= 3, "at least three pegs are required");
+    assert!(
+        src < num_pegs && dst < num_pegs && src != dst,
+        "source and destination must be distinct pegs"
+    );
+
+    let num_discs = num_discs as usize;
+    let spare: Vec<usize> = (0..num_pegs).filter(|&p| p != src && p != dst).collect();
+    let splits = frame_stewart_splits(num_discs, num_pegs);
+    let mut moves: Vec<IndexMove> = vec![];
+
+    mv_k(num_discs, &mut moves, src, dst, &spare, &splits);
+
+    moves
+}
@@ -0,0 +1,282 @@
+use crate::problem2::{Matrix, MatrixError, Numeric};
+
+/// A sparse matrix under construction, stored as (row, column, value)
+/// triplets in coordinate (COO) format.
+///
+/// Entries may be pushed in any order; duplicates are summed when the matrix
+/// is converted to a `CsrMatrix`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CooMatrix<T> {
+    rows: usize,
+    cols: usize,
+    entries: Vec<(usize, usize, T)>,
+}
+
+impl<T: Numeric> CooMatrix<T> {
+    /// Creates an empty `rows` x `cols` matrix.
+    pub fn new(rows: usize, cols: usize) -> CooMatrix<T> {
+        CooMatrix {
+            rows,
+            cols,
+            entries: vec![],
+        }
+    }
+
+    /// Adds `value` at (`row`, `col`).
+    pub fn push(&mut self, row: usize, col: usize, value: T) -> Result<(), MatrixError> {
+        if row >= self.rows || col >= self.cols {
+            return Err(MatrixError::OutOfBounds {
+                index: (row, col),
+                shape: (self.rows, self.cols),
+            });
+        }
+
+        self.entries.push((row, col, value));
+        Ok(())
+    }
+
+    /// Returns the dimensions as `(rows, cols)`.
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns the pushed triplets, in insertion order.
+    pub fn entries(&self) -> &[(usize, usize, T)] {
+        &self.entries
+    }
+
+    /// Converts to compressed sparse row format.
+    pub fn to_csr(&self) -> CsrMatrix<T> {
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|&(row, col, _)| (row, col));
+
+        let mut csr = CsrMatrix::empty(self.rows, self.cols);
+        let mut last = None;
+
+        for (row, col, value) in entries {
+            if last == Some((row, col)) {
+                let x = csr.values.last_mut().unwrap();
+                *x = *x + value;
+                continue;
+            }
+
+            csr.indptr[row + 1] += 1;
+            csr.indices.push(col);
+            csr.values.push(value);
+            last = Some((row, col));
+        }
+
+        for i in 0..self.rows {
+            csr.indptr[i + 1] += csr.indptr[i];
+        }
+
+        csr.drop_zeros();
+        csr
+    }
+}
+
+/// A sparse matrix in compressed sparse row (CSR) format.
+///
+/// The column indices and values of row `i` are stored at positions
+/// `indptr[i]..indptr[i + 1]`, with columns in increasing order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix<T> {
+    rows: usize,
+    cols: usize,
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T: Numeric> CsrMatrix<T> {
+    fn empty(rows: usize, cols: usize) -> CsrMatrix<T> {
+        CsrMatrix {
+            rows,
+            cols,
+            indptr: vec![0; rows + 1],
+            indices: vec![],
+            values: vec![],
+        }
+    }
+
+    /// Creates a sparse copy of `mat`, keeping only the non-zero entries.
+    pub fn from_dense(mat: &Matrix<T>) -> CsrMatrix<T> {
+        let (rows, cols) = mat.shape();
+        let mut csr = CsrMatrix::empty(rows, cols);
+
+        for i in 0..rows {
+            for (j, &x) in mat.row(i).iter().enumerate() {
+                if x != T::zero() {
+                    csr.indices.push(j);
+                    csr.values.push(x);
+                }
+            }
+            csr.indptr[i + 1] = csr.values.len();
+        }
+
+        csr
+    }
+
+    /// Creates a dense copy of the matrix.
+    pub fn to_dense(&self) -> Matrix<T> {
+        let mut mat = Matrix::zeros(self.rows, self.cols);
+
+        for (i, j, &x) in self.iter() {
+            mat[(i, j)] = x;
+        }
+
+        mat
+    }
+
+    /// Returns the dimensions as `(rows, cols)`.
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns the number of stored (non-zero) entries.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the element at (`row`, `col`), or `None` if out of bounds.
+    pub fn get(&self, row: usize, col: usize) -> Option<T> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+
+        let (cols, values) = self.row(row);
+
+        Some(match cols.binary_search(&col) {
+            Ok(k) => values[k],
+            Err(_) => T::zero(),
+        })
+    }
+
+    /// Iterates over the stored entries as `(row, col, &value)`, in row-major
+    /// order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> + '_ {
+        (0..self.rows).flat_map(move |i| {
+            let (cols, values) = self.row(i);
+            cols.iter().zip(values).map(move |(&j, x)| (i, j, x))
+        })
+    }
+
+    /// Returns the column indices and values stored in row `i`.
+    fn row(&self, i: usize) -> (&[usize], &[T]) {
+        let range = self.indptr[i]..self.indptr[i + 1];
+        (&self.indices[range.clone()], &self.values[range])
+    }
+
+    /// Computes the product of `self` and the dense matrix `other`.
+    pub fn mul_dense(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError> {
+        if self.cols != other.rows() {
+            return Err(MatrixError::DimensionMismatch {
+                left: self.shape(),
+                right: other.shape(),
+            });
+        }
+
+        let mut result = Matrix::zeros(self.rows, other.cols());
+
+        for i in 0..self.rows {
+            let (cols, values) = self.row(i);
+
+            for (&k, &a) in cols.iter().zip(values) {
+                for (j, &b) in other.row(k).iter().enumerate() {
+                    result[(i, j)] = result[(i, j)] + a * b;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Computes the product of `self` and `other`, both sparse.
+    pub fn mat_mult(&self, other: &CsrMatrix<T>) -> Result<CsrMatrix<T>, MatrixError> {
+        if self.cols != other.rows {
+            return Err(MatrixError::DimensionMismatch {
+                left: self.shape(),
+                right: other.shape(),
+            });
+        }
+
+        // Gustavson's algorithm: accumulate each output row in a dense
+        // buffer, remembering which columns were touched.
+        let mut result = CsrMatrix::empty(self.rows, other.cols);
+        let mut acc = vec![T::zero(); other.cols];
+        let mut touched = vec![false; other.cols];
+        let mut cols_in_row: Vec<usize> = vec![];
+
+        for i in 0..self.rows {
+            let (cols, values) = self.row(i);
+
+            for (&k, &a) in cols.iter().zip(values) {
+                let (other_cols, other_values) = other.row(k);
+
+                for (&j, &b) in other_cols.iter().zip(other_values) {
+                    if !touched[j] {
+                        touched[j] = true;
+                        cols_in_row.push(j);
+                    }
+                    acc[j] = acc[j] + a * b;
+                }
+            }
+
+            cols_in_row.sort_unstable();
+            for &j in &cols_in_row {
+                if acc[j] != T::zero() {
+                    result.indices.push(j);
+                    result.values.push(acc[j]);
+                }
+                acc[j] = T::zero();
+                touched[j] = false;
+            }
+            cols_in_row.clear();
+
+            result.indptr[i + 1] = result.values.len();
+        }
+
+        Ok(result)
+    }
+
+    /// Removes stored entries that are exactly zero.
+    fn drop_zeros(&mut self) {
+        let mut kept = 0;
+        let mut start = 0;
+
+        for i in 0..self.rows {
+            let end = self.indptr[i + 1];
+
+            for k in start..end {
+                if self.values[k] != T::zero() {
+                    self.indices[kept] = self.indices[k];
+                    self.values[kept] = self.values[k];
+                    kept += 1;
+                }
+            }
+            self.indptr[i + 1] = kept;
+            start = end;
+        }
+
+        self.indices.truncate(kept);
+        self.values.truncate(kept);
+    }
+}
+
+impl<'a, T: Numeric> From<&'a Matrix<T>> for CsrMatrix<T> {
+    fn from(mat: &'a Matrix<T>) -> CsrMatrix<T> {
+        CsrMatrix::from_dense(mat)
+    }
+}
+
+impl<'a, T: Numeric> From<&'a CsrMatrix<T>> for Matrix<T> {
+    fn from(mat: &'a CsrMatrix<T>) -> Matrix<T> {
+        mat.to_dense()
+    }
+}
+
+impl<'a, T: Numeric> From<&'a CooMatrix<T>> for CsrMatrix<T> {
+    fn from(mat: &'a CooMatrix<T>) -> CsrMatrix<T> {
+        mat.to_csr()
+    }
+}
@@ -0,0 +1,128 @@
+#![cfg(test)]
+
+extern crate hw01;
+extern crate proptest;
+
+use proptest::prelude::*;
+
+use hw01::problem1::{dedup, filter};
+use hw01::problem2::Matrix;
+use hw01::problem3::{primes_in_range, sieve};
+use hw01::problem4::{hanoi, hanoi_iter, verify, Peg};
+
+fn is_prime_reference(n: u64) -> bool {
+    n >= 2
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+
+/// Returns whether `sub` can be obtained from `vs` by removing elements.
+fn is_subsequence<T: PartialEq>(sub: &[T], vs: &[T]) -> bool {
+    let mut rest = vs.iter();
+    sub.iter().all(|x| rest.any(|y| x == y))
+}
+
+/// Matrices with small integer entries, so products never overflow.
+fn matrix(rows: usize, cols: usize) -> impl Strategy<Value = Matrix<i64>> {
+    prop::collection::vec(-50i64..50, rows * cols)
+        .prop_map(move |data| Matrix::new(rows, cols, data).unwrap())
+}
+
+fn peg() -> impl Strategy<Value = Peg> {
+    prop_oneof![Just(Peg::A), Just(Peg::B), Just(Peg::C)]
+}
+
+//
+// Problem 1
+//
+
+proptest! {
+    #[test]
+    fn prop_dedup_idempotent(vs in prop::collection::vec(-20i32..20, 0..100)) {
+        let once = dedup(&vs);
+        prop_assert_eq!(dedup(&once), once);
+    }
+
+    #[test]
+    fn prop_dedup_keeps_first_occurrences(vs in prop::collection::vec(-20i32..20, 0..100)) {
+        let result = dedup(&vs);
+
+        prop_assert!(is_subsequence(&result, &vs));
+        for (i, x) in result.iter().enumerate() {
+            prop_assert!(!result[..i].contains(x));
+        }
+        for x in &vs {
+            prop_assert!(result.contains(x));
+        }
+
+        let firsts: Vec<usize> = result
+            .iter()
+            .map(|x| vs.iter().position(|y| y == x).unwrap())
+            .collect();
+        prop_assert!(firsts.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn prop_filter_is_subset(vs in prop::collection::vec(any::<i32>(), 0..100), m in 1i32..10) {
+        let pred = |x: i32| x % m == 0;
+        let result = filter(&vs, pred);
+
+        prop_assert!(is_subsequence(&result, &vs));
+        prop_assert!(result.iter().all(|&x| pred(x)));
+        prop_assert_eq!(result.len(), vs.iter().filter(|&&x| pred(x)).count());
+    }
+}
+
+//
+// Problem 2
+//
+
+proptest! {
+    #[test]
+    fn prop_mat_mult_associative(
+        (a, b, c) in (0usize..6, 0usize..6, 0usize..6, 0usize..6)
+            .prop_flat_map(|(m, n, p, q)| (matrix(m, n), matrix(n, p), matrix(p, q)))
+    ) {
+        let left = a.mat_mult(&b).unwrap().mat_mult(&c).unwrap();
+        let right = a.mat_mult(&b.mat_mult(&c).unwrap()).unwrap();
+        prop_assert_eq!(left, right);
+    }
+}
+
+//
+// Problem 3
+//
+
+proptest! {
+    #[test]
+    fn prop_sieve_matches_reference(n in 0u32..5_000) {
+        let expected: Vec<u32> = (0..n).filter(|&k| is_prime_reference(k as u64)).collect();
+        prop_assert_eq!(sieve(n), expected);
+    }
+
+    #[test]
+    fn prop_primes_in_range_matches_reference(lo in 0u64..1_000_000, width in 0u64..2_000) {
+        let expected: Vec<u64> = (lo..lo + width).filter(|&k| is_prime_reference(k)).collect();
+        prop_assert_eq!(primes_in_range(lo, lo + width).collect::<Vec<_>>(), expected);
+    }
+}
+
+//
+// Problem 4
+//
+
+proptest! {
+    #[test]
+    fn prop_hanoi_moves_are_legal(
+        n in 0u32..12,
+        (src, aux, dst) in (peg(), peg(), peg())
+            .prop_filter("pegs must be distinct", |(a, b, c)| a != b && b != c && a != c)
+    ) {
+        let moves = hanoi(n, src, aux, dst);
+
+        prop_assert_eq!(moves.len(), (1usize << n) - 1);
+        prop_assert_eq!(verify(n, 3, src, dst, &moves), Ok(()));
+        prop_assert_eq!(hanoi_iter(n, src, aux, dst).collect::<Vec<_>>(), moves);
+    }
+}
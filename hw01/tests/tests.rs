@@ -2,7 +2,836 @@
 
 extern crate hw01;
 
-use hw01::problem4::{hanoi, Peg};
+use hw01::linalg::{self, LinalgError};
+use hw01::matrix_io::{self, MatrixIoError, MtxFormat};
+use hw01::problem1::{checked_sum, dedup, dedup_ord, filter, saturating_sum, sum};
+use hw01::problem2::{mat_mult, Matrix, MatrixError, MultStrategy};
+use hw01::problem3::{
+    factorize, is_prime, nth_prime, prime_count, primes_in_range, sieve, Primes, SEGMENT_BITS,
+};
+use hw01::problem4::{
+    hanoi, hanoi_iter, hanoi_k, verify, HanoiState, MoveError, Peg, PegIndex, VerifyError,
+};
+use hw01::sparse::{CooMatrix, CsrMatrix};
+
+//
+// Problem 1
+//
+
+#[test]
+fn test_sum_generic() {
+    assert_eq!(sum::<i32>(&[]), 0);
+    assert_eq!(sum(&[1u64 << 40, 1 << 40]), 1 << 41);
+    assert_eq!(sum(&[0.5f64, 0.25, 0.125]), 0.875);
+}
+
+#[test]
+fn test_checked_sum() {
+    assert_eq!(checked_sum::<i32>(&[]), Some(0));
+    assert_eq!(checked_sum(&[1, 2, 3]), Some(6));
+    assert_eq!(checked_sum(&[i32::MAX, 1]), None);
+    assert_eq!(checked_sum(&[i32::MIN, -1]), None);
+    assert_eq!(checked_sum(&[200u8, 55]), Some(255));
+    assert_eq!(checked_sum(&[200u8, 56]), None);
+}
+
+#[test]
+fn test_saturating_sum() {
+    assert_eq!(saturating_sum(&[1, 2, 3]), 6);
+    assert_eq!(saturating_sum(&[i32::MAX, 1, 1]), i32::MAX);
+    assert_eq!(saturating_sum(&[i32::MAX, 1, -1]), i32::MAX - 1);
+    assert_eq!(saturating_sum(&[i64::MIN, -5]), i64::MIN);
+    assert_eq!(saturating_sum(&[250u8, 10]), 255);
+}
+
+#[test]
+fn test_dedup_generic() {
+    let words = vec!["b", "a", "b", "c", "a"];
+    assert_eq!(dedup(&words), vec!["b", "a", "c"]);
+    assert_eq!(dedup_ord(&words), vec!["b", "a", "c"]);
+
+    let owned: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+    assert_eq!(dedup(&owned), vec!["b", "a", "c"]);
+    assert_eq!(dedup::<i32>(&[]), vec![]);
+}
+
+#[test]
+fn test_dedup_large() {
+    let vs: Vec<u32> = (0..200_000).map(|i| i % 1_000).collect();
+    let expected: Vec<u32> = (0..1_000).collect();
+    assert_eq!(dedup(&vs), expected);
+    assert_eq!(dedup_ord(&vs), expected);
+}
+
+#[test]
+fn test_filter_generic() {
+    let words = vec![
+        "apple".to_string(),
+        "kiwi".to_string(),
+        "banana".to_string(),
+    ];
+    assert_eq!(
+        filter(&words, |w: String| w.len() > 4),
+        vec!["apple", "banana"]
+    );
+    assert_eq!(filter(&[1.5, -2.0, 3.0], |x: f64| x > 0.0), vec![1.5, 3.0]);
+}
+
+//
+// Problem 2
+//
+
+#[test]
+fn test_matrix_new_checks_length() {
+    assert!(Matrix::new(2, 2, vec![1, 2, 3, 4]).is_ok());
+    assert_eq!(
+        Matrix::new(2, 2, vec![1, 2, 3]),
+        Err(MatrixError::DataLength {
+            expected: 4,
+            actual: 3
+        })
+    );
+}
+
+#[test]
+fn test_matrix_new_checks_overflow() {
+    assert_eq!(
+        Matrix::<i32>::new(1 << (usize::BITS - 1), 2, vec![]),
+        Err(MatrixError::TooLarge {
+            rows: 1 << (usize::BITS - 1),
+            cols: 2
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "too many elements")]
+fn test_matrix_zeros_overflow() {
+    Matrix::<i32>::zeros(usize::MAX, 2);
+}
+
+#[test]
+fn test_matrix_from_rows_ragged() {
+    let result = Matrix::from_rows(&[vec![1, 2], vec![3]]);
+    assert_eq!(
+        result,
+        Err(MatrixError::RaggedRow {
+            row: 1,
+            expected: 2,
+            actual: 1
+        })
+    );
+}
+
+#[test]
+fn test_matrix_index() {
+    let mut mat = Matrix::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    assert_eq!(mat.shape(), (2, 3));
+    assert_eq!(mat[(0, 2)], 3);
+    assert_eq!(mat[(1, 0)], 4);
+    assert_eq!(mat.get(2, 0), None);
+
+    mat[(1, 1)] = 50;
+    assert_eq!(mat.row(1), &[4, 50, 6]);
+}
+
+#[test]
+fn test_matrix_mult() {
+    let mat1 = Matrix::from_rows(&[vec![1i64, 2, 3], vec![4, 5, 6]]).unwrap();
+    let mat2 = Matrix::from_rows(&[vec![7i64, 8], vec![9, 10], vec![11, 12]]).unwrap();
+    let expected = Matrix::from_rows(&[vec![58i64, 64], vec![139, 154]]).unwrap();
+
+    assert_eq!(mat1.mat_mult(&mat2), Ok(expected.clone()));
+    assert_eq!(&mat1 * &mat2, expected);
+    assert_eq!(mat1.mat_mult(&Matrix::identity(3)), Ok(mat1.clone()));
+}
+
+#[test]
+fn test_matrix_mult_mismatch() {
+    let mat1: Matrix<f64> = Matrix::zeros(2, 3);
+    let mat2 = Matrix::zeros(2, 3);
+    assert_eq!(
+        mat1.mat_mult(&mat2),
+        Err(MatrixError::DimensionMismatch {
+            left: (2, 3),
+            right: (2, 3)
+        })
+    );
+}
+
+#[test]
+fn test_matrix_add_sub() {
+    let mat1 = Matrix::from_rows(&[vec![1, 2], vec![3, 4]]).unwrap();
+    let mat2 = Matrix::from_rows(&[vec![10, 20], vec![30, 40]]).unwrap();
+
+    assert_eq!(
+        &mat1 + &mat2,
+        Matrix::from_rows(&[vec![11, 22], vec![33, 44]]).unwrap()
+    );
+    assert_eq!(
+        mat2.clone() - mat1.clone(),
+        Matrix::from_rows(&[vec![9, 18], vec![27, 36]]).unwrap()
+    );
+    assert!(mat1.checked_add(&Matrix::zeros(1, 2)).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_matrix_add_mismatch_panics() {
+    let _ = Matrix::<i32>::zeros(1, 2) + Matrix::zeros(2, 1);
+}
+
+#[test]
+fn test_mat_mult_empty() {
+    assert_eq!(mat_mult(&[], &[]), Vec::<Vec<f32>>::new());
+}
+
+#[test]
+fn test_mat_mult_rectangular() {
+    let mat1 = vec![vec![1., 2.]];
+    let mat2 = vec![vec![3.], vec![4.]];
+    assert_eq!(mat_mult(&mat1, &mat2), vec![vec![11.]]);
+}
+
+/// Builds a matrix of small integer values from a fixed linear congruential
+/// sequence, so products are exact in every numeric type.
+fn integer_matrix<T: hw01::problem2::Numeric>(
+    rows: usize,
+    cols: usize,
+    seed: u64,
+    from: fn(i64) -> T,
+) -> Matrix<T> {
+    let mut state = seed;
+    let data = (0..rows * cols)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            from((state >> 59) as i64 - 16)
+        })
+        .collect();
+    Matrix::new(rows, cols, data).unwrap()
+}
+
+const STRATEGIES: [MultStrategy; 5] = [
+    MultStrategy::Blocked,
+    MultStrategy::Parallel { threads: 0 },
+    MultStrategy::Parallel { threads: 1 },
+    MultStrategy::Parallel { threads: 3 },
+    MultStrategy::Parallel { threads: 64 },
+];
+
+#[test]
+fn test_mat_mult_strategies_agree_f64() {
+    // Dimensions deliberately straddle the tile size.
+    let mat1 = integer_matrix(130, 97, 1, |x| x as f64);
+    let mat2 = integer_matrix(97, 71, 2, |x| x as f64);
+    let expected = mat1.mat_mult_with(&mat2, MultStrategy::Naive).unwrap();
+
+    for &strategy in STRATEGIES.iter() {
+        let result = mat1.mat_mult_with(&mat2, strategy).unwrap();
+        let same_bits = result
+            .as_slice()
+            .iter()
+            .zip(expected.as_slice())
+            .all(|(a, b)| a.to_bits() == b.to_bits());
+        assert!(same_bits, "{:?} differs from naive", strategy);
+    }
+}
+
+#[test]
+fn test_mat_mult_strategies_agree_i64() {
+    let mat1 = integer_matrix(65, 129, 3, |x| x);
+    let mat2 = integer_matrix(129, 64, 4, |x| x);
+    let expected = mat1.mat_mult_with(&mat2, MultStrategy::Naive).unwrap();
+
+    for &strategy in STRATEGIES.iter() {
+        assert_eq!(mat1.mat_mult_with(&mat2, strategy), Ok(expected.clone()));
+    }
+}
+
+#[test]
+fn test_mat_mult_strategies_degenerate() {
+    let mat1: Matrix<i32> = Matrix::zeros(3, 0);
+    let mat2 = Matrix::zeros(0, 2);
+
+    for &strategy in STRATEGIES.iter() {
+        assert_eq!(mat1.mat_mult_with(&mat2, strategy), Ok(Matrix::zeros(3, 2)));
+        assert!(mat2.mat_mult_with(&mat1, strategy).is_err());
+    }
+}
+
+//
+// Sparse matrices
+//
+
+/// Like `integer_matrix`, but with roughly nine zeros for every non-zero.
+fn sparse_integer_matrix(rows: usize, cols: usize, seed: u64) -> Matrix<i64> {
+    let mat = integer_matrix(rows, cols, seed, |x| x);
+    let data = mat
+        .as_slice()
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            if (i as u64 * 7 + seed).is_multiple_of(10) {
+                x
+            } else {
+                0
+            }
+        })
+        .collect();
+    Matrix::new(rows, cols, data).unwrap()
+}
+
+#[test]
+fn test_coo_to_csr() {
+    let mut coo = CooMatrix::new(3, 4);
+    coo.push(2, 1, 5).unwrap();
+    coo.push(0, 3, 1).unwrap();
+    coo.push(2, 1, 2).unwrap();
+    coo.push(1, 0, 4).unwrap();
+    coo.push(1, 2, 0).unwrap();
+    assert_eq!(
+        coo.push(3, 0, 1),
+        Err(MatrixError::OutOfBounds {
+            index: (3, 0),
+            shape: (3, 4)
+        })
+    );
+
+    let csr = coo.to_csr();
+    assert_eq!(csr.nnz(), 3);
+    assert_eq!(
+        csr.iter().map(|(i, j, &x)| (i, j, x)).collect::<Vec<_>>(),
+        vec![(0, 3, 1), (1, 0, 4), (2, 1, 7)]
+    );
+    assert_eq!(csr.get(2, 1), Some(7));
+    assert_eq!(csr.get(1, 1), Some(0));
+    assert_eq!(csr.get(3, 1), None);
+}
+
+#[test]
+fn test_coo_duplicates_cancel() {
+    let mut coo = CooMatrix::new(2, 2);
+    coo.push(0, 0, 3).unwrap();
+    coo.push(0, 0, -3).unwrap();
+    coo.push(1, 1, 1).unwrap();
+
+    let csr = CsrMatrix::from(&coo);
+    assert_eq!(csr.nnz(), 1);
+    assert_eq!(
+        csr.to_dense(),
+        Matrix::from_rows(&[vec![0, 0], vec![0, 1]]).unwrap()
+    );
+}
+
+#[test]
+fn test_csr_dense_round_trip() {
+    let dense = sparse_integer_matrix(17, 23, 5);
+    let csr = CsrMatrix::from_dense(&dense);
+    assert!(csr.nnz() < 17 * 23 / 5);
+    assert_eq!(Matrix::from(&csr), dense);
+    assert_eq!(CsrMatrix::from(&Matrix::<f32>::zeros(0, 3)).shape(), (0, 3));
+}
+
+#[test]
+fn test_csr_mul_dense() {
+    let lhs = sparse_integer_matrix(40, 30, 1);
+    let rhs = integer_matrix(30, 20, 2, |x| x);
+    let expected = lhs.mat_mult(&rhs).unwrap();
+
+    assert_eq!(CsrMatrix::from(&lhs).mul_dense(&rhs), Ok(expected));
+    assert!(CsrMatrix::from(&lhs).mul_dense(&lhs).is_err());
+}
+
+#[test]
+fn test_csr_mat_mult() {
+    let lhs = sparse_integer_matrix(40, 30, 3);
+    let rhs = sparse_integer_matrix(30, 50, 4);
+    let expected = lhs.mat_mult(&rhs).unwrap();
+
+    let product = CsrMatrix::from(&lhs)
+        .mat_mult(&CsrMatrix::from(&rhs))
+        .unwrap();
+    assert_eq!(product, CsrMatrix::from(&expected));
+    assert_eq!(product.to_dense(), expected);
+
+    assert_eq!(
+        CsrMatrix::from(&lhs).mat_mult(&CsrMatrix::from(&lhs)),
+        Err(MatrixError::DimensionMismatch {
+            left: (40, 30),
+            right: (40, 30)
+        })
+    );
+}
+
+//
+// Matrix files
+//
+
+fn parse_error_position<T>(result: Result<T, MatrixIoError>) -> (usize, usize) {
+    match result {
+        Err(MatrixIoError::Parse { line, column, .. }) => (line, column),
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("expected a parse error"),
+    }
+}
+
+#[test]
+fn test_parse_csv() {
+    let mat: Matrix<f64> = matrix_io::parse_csv("1, 2.5,3\n\n-4,5,6e1\n").unwrap();
+    assert_eq!(
+        mat,
+        Matrix::from_rows(&[vec![1., 2.5, 3.], vec![-4., 5., 60.]]).unwrap()
+    );
+    assert_eq!(
+        matrix_io::parse_csv::<i32>("").unwrap(),
+        Matrix::zeros(0, 0)
+    );
+}
+
+#[test]
+fn test_parse_csv_errors() {
+    let result = matrix_io::parse_csv::<i32>("1,2\n3, x\n");
+    assert_eq!(parse_error_position(result), (2, 4));
+
+    let result = matrix_io::parse_csv::<i32>("1,2\n3,4\n5\n");
+    assert_eq!(parse_error_position(result), (3, 1));
+
+    let result = matrix_io::parse_csv::<i32>("1,,2\n");
+    assert_eq!(parse_error_position(result), (1, 3));
+
+    // Columns count characters, not bytes.
+    let result = matrix_io::parse_csv::<i32>("1, 2\n3,\u{a0}\u{e9}\n");
+    assert_eq!(parse_error_position(result), (2, 4));
+}
+
+#[test]
+fn test_csv_round_trip() {
+    let mat = integer_matrix(7, 5, 9, |x| x as f32 / 4.0);
+    let mut out = vec![];
+    matrix_io::write_csv(&mat, &mut out).unwrap();
+    assert_eq!(matrix_io::read_csv(&out[..]).unwrap(), mat);
+}
+
+#[test]
+fn test_parse_matrix_market_coordinate() {
+    let input = "%%MatrixMarket matrix coordinate real general
+% a comment
+3 2 3
+1 1 1.5
+3 2 -2
+2 1 4
+";
+    let mat: Matrix<f64> = matrix_io::parse_matrix_market(input).unwrap();
+    assert_eq!(
+        mat,
+        Matrix::from_rows(&[vec![1.5, 0.], vec![4., 0.], vec![0., -2.]]).unwrap()
+    );
+}
+
+#[test]
+fn test_parse_matrix_market_symmetric() {
+    let input = "%%MatrixMarket matrix coordinate integer symmetric
+2 2 2
+1 1 3
+2 1 7
+";
+    let mat: Matrix<i32> = matrix_io::parse_matrix_market(input).unwrap();
+    assert_eq!(mat, Matrix::from_rows(&[vec![3, 7], vec![7, 0]]).unwrap());
+
+    let input = "%%MatrixMarket matrix array integer skew-symmetric
+3 3
+1 2
+3
+";
+    let mat: Matrix<i32> = matrix_io::parse_matrix_market(input).unwrap();
+    assert_eq!(
+        mat,
+        Matrix::from_rows(&[vec![0, -1, -2], vec![1, 0, -3], vec![2, 3, 0]]).unwrap()
+    );
+}
+
+#[test]
+fn test_parse_matrix_market_array() {
+    let input = "%%MatrixMarket matrix array real general
+2 3
+1 2
+3 4
+5 6
+";
+    let mat: Matrix<f32> = matrix_io::parse_matrix_market(input).unwrap();
+    assert_eq!(
+        mat,
+        Matrix::from_rows(&[vec![1., 3., 5.], vec![2., 4., 6.]]).unwrap()
+    );
+}
+
+#[test]
+fn test_parse_matrix_market_errors() {
+    let result =
+        matrix_io::parse_matrix_market::<f64>("%%MatrixMarket matrix coordinate complex general\n");
+    assert_eq!(parse_error_position(result), (1, 34));
+
+    let result = matrix_io::parse_matrix_market::<f64>(
+        "%%MatrixMarket matrix coordinate real general\n2 2 1\n3 1 1.0\n",
+    );
+    assert_eq!(parse_error_position(result), (3, 1));
+
+    let result = matrix_io::parse_matrix_market::<f64>(
+        "%%MatrixMarket matrix coordinate real general\n2 2 1\n1 1  abc\n",
+    );
+    assert_eq!(parse_error_position(result), (3, 6));
+
+    let result = matrix_io::parse_matrix_market::<f64>(
+        "%%MatrixMarket matrix array real general\n2 2\n1 2 3\n4 5\n",
+    );
+    assert_eq!(parse_error_position(result), (4, 3));
+
+    let result = matrix_io::parse_matrix_market::<f64>("%%MatrixMarket vector\n");
+    assert_eq!(parse_error_position(result), (1, 1));
+
+    // Symmetric files only store the lower triangle.
+    let result = matrix_io::parse_matrix_market::<i32>(
+        "%%MatrixMarket matrix coordinate integer symmetric\n2 2 2\n1 1 3\n 1 2 7\n",
+    );
+    assert_eq!(parse_error_position(result), (4, 2));
+
+    let result = matrix_io::parse_matrix_market::<i32>(
+        "%%MatrixMarket matrix coordinate integer skew-symmetric\n2 2 1\n2 2 3\n",
+    );
+    assert_eq!(parse_error_position(result), (3, 1));
+}
+
+#[test]
+fn test_parse_matrix_market_huge_sizes() {
+    // The element count overflows a `usize`.
+    let result = matrix_io::parse_matrix_market::<f64>(&format!(
+        "%%MatrixMarket matrix coordinate real general\n{0} {0} 0\n",
+        usize::MAX / 2
+    ));
+    assert_eq!(parse_error_position(result), (2, 1));
+
+    // The declared size is only checked against the values, not allocated.
+    let result = matrix_io::parse_matrix_market::<f64>(
+        "%%MatrixMarket matrix array real general\n100000 100000\n1.0\n",
+    );
+    assert_eq!(parse_error_position(result), (3, 1));
+
+    // Too large to allocate, even though the file holds no entries.
+    let result = matrix_io::parse_matrix_market::<f64>(&format!(
+        "%%MatrixMarket matrix coordinate real general\n{0} 1 0\n",
+        usize::MAX / 8
+    ));
+    assert_eq!(parse_error_position(result), (2, 1));
+}
+
+#[test]
+fn test_matrix_market_round_trip() {
+    let mat = sparse_integer_matrix(9, 11, 2);
+
+    for &format in [MtxFormat::Coordinate, MtxFormat::Array].iter() {
+        let mut out = vec![];
+        matrix_io::write_matrix_market(&mat, format, &mut out).unwrap();
+        assert_eq!(matrix_io::read_matrix_market(&out[..]).unwrap(), mat);
+    }
+}
+
+#[test]
+fn test_matmul_binary() {
+    let dir = std::env::temp_dir().join(format!("hw01-matmul-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (lhs, rhs, out) = (dir.join("a.csv"), dir.join("b.mtx"), dir.join("c.mtx"));
+
+    std::fs::write(&lhs, "1,2\n3,4\n").unwrap();
+    std::fs::write(
+        &rhs,
+        "%%MatrixMarket matrix coordinate real general\n2 1 2\n1 1 5\n2 1 6\n",
+    )
+    .unwrap();
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_matmul"))
+        .args([&lhs, &rhs, &out])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let product: Matrix<f64> =
+        matrix_io::read_matrix_market(std::fs::File::open(&out).unwrap()).unwrap();
+    assert_eq!(product, Matrix::from_rows(&[vec![17.], vec![39.]]).unwrap());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_matmul"))
+        .args([&rhs, &lhs])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+//
+// Linear algebra
+//
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+    }
+}
+
+#[test]
+fn test_transpose() {
+    let mat = Matrix::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    let expected = Matrix::from_rows(&[vec![1, 4], vec![2, 5], vec![3, 6]]).unwrap();
+    assert_eq!(linalg::transpose(&mat), expected);
+    assert_eq!(linalg::transpose(&expected), mat);
+}
+
+#[test]
+fn test_lu_reconstructs_matrix() {
+    let mat = Matrix::from_rows(&[vec![2., 1., 1.], vec![4., -6., 0.], vec![-2., 7., 2.]]).unwrap();
+    let lu = linalg::lu(&mat).unwrap();
+
+    let mut permuted = Matrix::zeros(3, 3);
+    for (i, &p) in lu.permutation().iter().enumerate() {
+        for j in 0..3 {
+            permuted[(i, j)] = mat[(p, j)];
+        }
+    }
+
+    let product = lu.l().mat_mult(&lu.u()).unwrap();
+    assert_close(product.as_slice(), permuted.as_slice());
+}
+
+#[test]
+fn test_determinant() {
+    let mat = Matrix::from_rows(&[vec![0., 2.], vec![3., 4.]]).unwrap();
+    assert_close(&[linalg::determinant(&mat).unwrap()], &[-6.]);
+
+    let mat = Matrix::from_rows(&[vec![6., 1., 1.], vec![4., -2., 5.], vec![2., 8., 7.]]).unwrap();
+    assert_close(&[linalg::determinant(&mat).unwrap()], &[-306.]);
+
+    let singular = Matrix::from_rows(&[vec![1., 2.], vec![2., 4.]]).unwrap();
+    assert_eq!(linalg::determinant(&singular), Ok(0.));
+}
+
+#[test]
+fn test_inverse() {
+    let mat = Matrix::from_rows(&[vec![4., 7.], vec![2., 6.]]).unwrap();
+    let inv = linalg::inverse(&mat).unwrap();
+    assert_close(inv.as_slice(), &[0.6, -0.7, -0.2, 0.4]);
+    assert_close(
+        mat.mat_mult(&inv).unwrap().as_slice(),
+        Matrix::<f64>::identity(2).as_slice(),
+    );
+}
+
+#[test]
+fn test_solve() {
+    let a = Matrix::from_rows(&[vec![2., 1., -1.], vec![-3., -1., 2.], vec![-2., 1., 2.]]).unwrap();
+    assert_close(
+        &linalg::solve(&a, &[8., -11., -3.]).unwrap(),
+        &[2., 3., -1.],
+    );
+    assert_eq!(
+        linalg::solve(&a, &[1., 2.]),
+        Err(LinalgError::DimensionMismatch {
+            expected: 3,
+            actual: 2
+        })
+    );
+}
+
+#[test]
+fn test_linalg_errors() {
+    let singular = Matrix::from_rows(&[vec![1., 2.], vec![2., 4.]]).unwrap();
+    assert_eq!(linalg::inverse(&singular), Err(LinalgError::Singular));
+    assert_eq!(
+        linalg::solve(&singular, &[1., 1.]),
+        Err(LinalgError::Singular)
+    );
+
+    let rect: Matrix<f64> = Matrix::zeros(2, 3);
+    let not_square = Err(LinalgError::NotSquare { rows: 2, cols: 3 });
+    assert_eq!(linalg::determinant(&rect), not_square);
+    assert_eq!(linalg::inverse(&rect).map(|_| 0.), not_square);
+}
+
+#[test]
+fn test_linalg_badly_scaled() {
+    let mat = Matrix::from_rows(&[vec![1., 0.], vec![0., 1e-20]]).unwrap();
+    assert_eq!(linalg::determinant(&mat), Ok(1e-20));
+    assert_eq!(
+        linalg::inverse(&mat).unwrap().as_slice(),
+        &[1., 0., 0., 1e20]
+    );
+    assert_close(&linalg::solve(&mat, &[2., 3e-20]).unwrap(), &[2., 3.]);
+}
+
+//
+// Problem 3
+//
+
+fn is_prime_reference(n: u64) -> bool {
+    n >= 2
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+
+#[test]
+fn test_sieve_small() {
+    assert_eq!(sieve(0), vec![]);
+    assert_eq!(sieve(2), vec![]);
+    assert_eq!(sieve(3), vec![2]);
+    assert_eq!(sieve(7), vec![2, 3, 5]);
+    assert_eq!(sieve(8), vec![2, 3, 5, 7]);
+}
+
+#[test]
+fn test_sieve_matches_reference() {
+    let expected: Vec<u32> = (0..10_000u32)
+        .filter(|&n| is_prime_reference(n as u64))
+        .collect();
+    assert_eq!(sieve(10_000), expected);
+}
+
+#[test]
+fn test_sieve_counts() {
+    assert_eq!(sieve(1_000_000).len(), 78_498);
+    assert_eq!(sieve(10_000_000).len(), 664_579);
+}
+
+#[test]
+#[ignore]
+fn test_sieve_billion() {
+    assert_eq!(sieve(1_000_000_000).len(), 50_847_534);
+}
+
+#[test]
+fn test_primes_in_range_small() {
+    assert_eq!(
+        primes_in_range(0, 12).collect::<Vec<_>>(),
+        vec![2, 3, 5, 7, 11]
+    );
+    assert_eq!(primes_in_range(2, 3).collect::<Vec<_>>(), vec![2]);
+    assert_eq!(primes_in_range(3, 3).count(), 0);
+    assert_eq!(primes_in_range(10, 5).count(), 0);
+    assert_eq!(primes_in_range(14, 17).count(), 0);
+    assert_eq!(primes_in_range(13, 18).collect::<Vec<_>>(), vec![13, 17]);
+}
+
+#[test]
+fn test_primes_in_range_matches_sieve() {
+    let all = sieve(3_000_000);
+    // Ranges spanning several segments, starting on odd and even numbers.
+    let bounds = [
+        (0, 3_000_000),
+        (1, 2 * SEGMENT_BITS as u32 + 1),
+        (1_000_000, 2_000_001),
+        (999_999, 1_000_037),
+    ];
+
+    for &(lo, hi) in bounds.iter() {
+        let expected: Vec<u64> = all
+            .iter()
+            .filter(|&&p| lo <= p && p < hi)
+            .map(|&p| p as u64)
+            .collect();
+        assert_eq!(
+            primes_in_range(lo as u64, hi as u64).collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_primes_in_range_large() {
+    let lo = 1_000_000_000_000;
+    let expected: Vec<u64> = (lo..lo + 2_000)
+        .filter(|&n| is_prime_reference(n))
+        .collect();
+    assert_eq!(
+        primes_in_range(lo, lo + 2_000).collect::<Vec<_>>(),
+        expected
+    );
+}
+
+#[test]
+fn test_primes_iterator() {
+    let expected: Vec<u64> = sieve(5_000_000).into_iter().map(|p| p as u64).collect();
+    let result: Vec<u64> = Primes::new().take(expected.len()).collect();
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_nth_prime() {
+    assert_eq!(nth_prime(0), 2);
+    assert_eq!(nth_prime(1), 3);
+    assert_eq!(nth_prime(9), 29);
+    assert_eq!(nth_prime(9_999), 104_729);
+    assert_eq!(nth_prime(999_999), 15_485_863);
+}
+
+#[test]
+fn test_prime_count() {
+    assert_eq!(prime_count(0), 0);
+    assert_eq!(prime_count(2), 1);
+    assert_eq!(prime_count(10), 4);
+    assert_eq!(prime_count(11), 5);
+    assert_eq!(prime_count(1_000_000), 78_498);
+}
+
+#[test]
+fn test_is_prime() {
+    for n in 0..10_000 {
+        assert_eq!(is_prime(n), is_prime_reference(n), "{}", n);
+    }
+
+    assert!(is_prime(1_000_000_007));
+    assert!(is_prime(18_446_744_073_709_551_557));
+    assert!(!is_prime(u64::MAX));
+    // Strong pseudoprimes to several small bases.
+    assert!(!is_prime(3_215_031_751));
+    assert!(!is_prime(3_825_123_056_546_413_051));
+    assert!(!is_prime(4_294_967_297));
+}
+
+#[test]
+fn test_factorize() {
+    assert_eq!(factorize(0), vec![]);
+    assert_eq!(factorize(1), vec![]);
+    assert_eq!(factorize(2), vec![(2, 1)]);
+    assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+    assert_eq!(factorize(4_294_967_297), vec![(641, 1), (6_700_417, 1)]);
+    assert_eq!(
+        factorize(1_000_000_007 * 998_244_353),
+        vec![(998_244_353, 1), (1_000_000_007, 1)]
+    );
+    assert_eq!(
+        factorize(4_294_967_291 * 4_294_967_291),
+        vec![(4_294_967_291, 2)]
+    );
+    assert_eq!(
+        factorize(u64::MAX),
+        vec![
+            (3, 1),
+            (5, 1),
+            (17, 1),
+            (257, 1),
+            (641, 1),
+            (65_537, 1),
+            (6_700_417, 1)
+        ]
+    );
+
+    for n in 2..5_000u64 {
+        let product: u64 = factorize(n).iter().map(|&(p, e)| p.pow(e)).product();
+        assert_eq!(product, n);
+        assert!(factorize(n).iter().all(|&(p, _)| is_prime(p)));
+    }
+}
 
 //
 // Problem 4
@@ -23,3 +852,132 @@ fn test_hanoi_3_disks() {
     assert_eq!(expected, result);
     assert_eq!(expected.len(), result.len());
 }
+
+#[test]
+fn test_hanoi_state_apply() {
+    let mut state = HanoiState::new(2, 3, Peg::A);
+    assert_eq!(state.pegs(), &[vec![2, 1], vec![], vec![]]);
+
+    assert_eq!(state.apply((Peg::A, Peg::B)), Ok(()));
+    assert_eq!(
+        state.apply((Peg::A, Peg::B)),
+        Err(MoveError::LargerOnSmaller { disc: 2, onto: 1 })
+    );
+    assert_eq!(state.apply((Peg::C, Peg::A)), Err(MoveError::EmptyPeg(2)));
+    assert_eq!(state.apply((0, 3)), Err(MoveError::NoSuchPeg(3)));
+    assert_eq!(state.pegs(), &[vec![2], vec![1], vec![]]);
+    assert!(!state.is_solved(Peg::C));
+}
+
+#[test]
+fn test_verify() {
+    let moves = hanoi(4, Peg::A, Peg::B, Peg::C);
+    assert_eq!(verify(4, 3, Peg::A, Peg::C, &moves), Ok(()));
+    assert_eq!(
+        verify(4, 3, Peg::A, Peg::B, &moves),
+        Err(VerifyError::Unsolved)
+    );
+    assert_eq!(
+        verify(4, 3, Peg::A, Peg::C, &moves[..moves.len() - 1]),
+        Err(VerifyError::Unsolved)
+    );
+
+    assert_eq!(
+        verify(4, 3, Peg::A, Peg::C, &[(Peg::A, Peg::B), (Peg::A, Peg::B)]),
+        Err(VerifyError::IllegalMove {
+            index: 1,
+            error: MoveError::LargerOnSmaller { disc: 2, onto: 1 }
+        })
+    );
+
+    assert_eq!(verify(2, 3, 3, 2, &[]), Err(VerifyError::NoSuchPeg(3)));
+    assert_eq!(verify(0, 3, 0, 5, &[]), Err(VerifyError::NoSuchPeg(5)));
+}
+
+#[test]
+fn test_hanoi_k_three_pegs_matches_hanoi() {
+    for n in 0..10 {
+        let expected: Vec<(usize, usize)> = hanoi(n, Peg::A, Peg::B, Peg::C)
+            .into_iter()
+            .map(|(src, dst)| (src.index(), dst.index()))
+            .collect();
+        assert_eq!(hanoi_k(n, 3, 0, 2), expected);
+    }
+}
+
+#[test]
+fn test_hanoi_k_frame_stewart_lengths() {
+    let four_pegs = [0, 1, 3, 5, 9, 13, 17, 25, 33, 41, 49];
+    let five_pegs = [0, 1, 3, 5, 7, 11, 15, 19, 23, 27, 31];
+
+    for n in 0..four_pegs.len() {
+        let moves = hanoi_k(n as u32, 4, 0, 3);
+        assert_eq!(moves.len(), four_pegs[n]);
+        assert_eq!(verify(n as u32, 4, 0, 3, &moves), Ok(()));
+
+        let moves = hanoi_k(n as u32, 5, 4, 1);
+        assert_eq!(moves.len(), five_pegs[n]);
+        assert_eq!(verify(n as u32, 5, 4, 1, &moves), Ok(()));
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_hanoi_k_needs_three_pegs() {
+    hanoi_k(3, 2, 0, 1);
+}
+
+#[test]
+fn test_hanoi_iter_matches_hanoi() {
+    let pegs = [Peg::A, Peg::B, Peg::C];
+
+    for n in 0..12 {
+        for &src in pegs.iter() {
+            for &dst in pegs.iter().filter(|&&p| p != src) {
+                let aux = *pegs.iter().find(|&&p| p != src && p != dst).unwrap();
+                let expected = hanoi(n, src, aux, dst);
+                let moves: Vec<_> = hanoi_iter(n, src, aux, dst).collect();
+                assert_eq!(moves, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hanoi_iter_nth() {
+    let expected = hanoi(10, Peg::A, Peg::B, Peg::C);
+
+    for (k, &mv) in expected.iter().enumerate() {
+        assert_eq!(hanoi_iter(10, Peg::A, Peg::B, Peg::C).nth(k), Some(mv));
+    }
+
+    let mut moves = hanoi_iter(10, Peg::A, Peg::B, Peg::C);
+    assert_eq!(moves.nth(5), Some(expected[5]));
+    assert_eq!(moves.next(), Some(expected[6]));
+    assert_eq!(
+        moves.size_hint(),
+        (expected.len() - 7, Some(expected.len() - 7))
+    );
+    assert_eq!(moves.nth(expected.len()), None);
+    assert_eq!(moves.next(), None);
+}
+
+#[test]
+fn test_hanoi_iter_large() {
+    let mut moves = hanoi_iter(64, Peg::A, Peg::B, Peg::C);
+    assert_eq!(moves.total(), u64::MAX);
+    assert_eq!(moves.next(), Some((Peg::A, Peg::B)));
+
+    // The middle move carries the largest disc straight to the target.
+    let mut moves = hanoi_iter(40, Peg::A, Peg::B, Peg::C);
+    assert_eq!(moves.nth((1 << 39) - 1), Some((Peg::A, Peg::C)));
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn test_hanoi_iter_large_last_move() {
+    // The last move brings the smallest disc from `aux` onto the target.
+    let mut moves = hanoi_iter(64, Peg::A, Peg::B, Peg::C);
+    assert_eq!(moves.nth((u64::MAX - 1) as usize), Some((Peg::B, Peg::C)));
+    assert_eq!(moves.next(), None);
+}
@@ -1,3 +1,6 @@
+use std::iter::FromIterator;
+use std::mem;
+
 #[derive(Debug)]
 struct Node {
     element: i32,
@@ -11,105 +14,427 @@ enum Link {
     More(Box<Node>),
 }
 
+/// A set of `i32`s stored as a binary search tree. Every node keeps smaller
+/// elements in its `left` subtree and larger ones in its `right` subtree.
 #[derive(Debug)]
 pub struct BST {
-    root: Node,
+    root: Link,
 }
 
 impl BST {
     pub fn new() -> BST {
-        BST {
-            root: Node {
-                element: 0,
-                left: Link::Empty,
-                right: Link::Empty,
-            },
-        }
+        BST { root: Link::Empty }
     }
 
     fn _create_node(element: i32) -> Node {
         Node {
-            element: element,
+            element,
             left: Link::Empty,
             right: Link::Empty,
         }
     }
 
-    fn _insert(node: &mut Node, _element: i32) -> bool {
-        match node {
-            Node {
-                element,..
-            } if *element == _element => false,
-            Node {
-                element,
-                left,
-                right,
-            } => {
-                if *element > _element {
-                    match right {
-                        Link::Empty => {
-                            node.right = Link::More(Box::new(BST::_create_node(_element)));
-                            true
-                        },
-                        Link::More(link_node) => {
-                            BST::_insert(link_node, _element)
-                        }
-                    }                    
-                }
-                else {
-                    match left {
-                        Link::Empty => {
-                            node.left = Link::More(Box::new(BST::_create_node(_element)));
-                            true
-                        },
-                        Link::More(link_node) => {
-                            BST::_insert(link_node, _element)
-                        }
-                    } 
-                }
-            },
-        }
-    }
-
-    fn _find(node: &mut Node, _element: i32) -> bool {
-        match node {
-            Node {
-                element,..
-            } if *element == _element => true,
-            Node {
-                element,
-                left,
-                right,
-            } => {
-                if *element > _element {
-                    match right {
-                        Link::Empty => {
-                            false
-                        },
-                        Link::More(link_node) => {
-                            BST::_find(link_node, _element)
-                        }
-                    }                    
-                }
-                else {
-                    match left {
-                        Link::Empty => {
-                            false
-                        },
-                        Link::More(link_node) => {
-                            BST::_find(link_node, _element)
-                        }
-                    } 
+    /// Walks down from `link` to where `_element` is or would be stored.
+    fn _locate(link: &mut Link, _element: i32) -> &mut Link {
+        let mut link = link;
+
+        loop {
+            let element = match *link {
+                Link::More(ref node) => node.element,
+                Link::Empty => return link,
+            };
+            if _element == element {
+                return link;
+            }
+
+            link = match link {
+                Link::More(node) => {
+                    if _element < element {
+                        &mut node.left
+                    } else {
+                        &mut node.right
+                    }
                 }
-            },
+                Link::Empty => unreachable!(),
+            };
+        }
+    }
+
+    fn _insert(link: &mut Link, _element: i32) -> bool {
+        let link = BST::_locate(link, _element);
+
+        match link {
+            Link::More(_) => false,
+            Link::Empty => {
+                *link = Link::More(Box::new(BST::_create_node(_element)));
+                true
+            }
         }
     }
 
+    fn _find(link: &Link, _element: i32) -> bool {
+        let mut link = link;
+
+        while let Link::More(node) = link {
+            if _element < node.element {
+                link = &node.left;
+            } else if _element > node.element {
+                link = &node.right;
+            } else {
+                return true;
+            }
+        }
+
+        false
+    }
+
     pub fn insert(&mut self, element: i32) -> bool {
         BST::_insert(&mut self.root, element)
     }
 
-    pub fn find(&mut self, element: i32) -> bool {
-        BST::_find(&mut self.root, element)
+    pub fn find(&self, element: i32) -> bool {
+        BST::_find(&self.root, element)
+    }
+
+    fn _len(link: &Link) -> usize {
+        let mut stack = vec![link];
+        let mut len = 0;
+
+        while let Some(link) = stack.pop() {
+            if let Link::More(node) = link {
+                len += 1;
+                stack.push(&node.left);
+                stack.push(&node.right);
+            }
+        }
+
+        len
+    }
+
+    fn _height(link: &Link) -> usize {
+        let mut stack = vec![(link, 0)];
+        let mut height = 0;
+
+        while let Some((link, depth)) = stack.pop() {
+            if let Link::More(node) = link {
+                height = height.max(depth + 1);
+                stack.push((&node.left, depth + 1));
+                stack.push((&node.right, depth + 1));
+            }
+        }
+
+        height
+    }
+
+    /// Returns the smallest (or largest) element of the subtree at `link`.
+    fn _extreme(link: &Link, smallest: bool) -> Option<i32> {
+        let mut link = link;
+        let mut best = None;
+
+        while let Link::More(node) = link {
+            best = Some(node.element);
+            link = if smallest { &node.left } else { &node.right };
+        }
+
+        best
+    }
+
+    /// Unlinks the smallest node of the non-empty subtree `link` and returns
+    /// its element.
+    fn _take_min(link: &mut Link) -> i32 {
+        let mut link = link;
+
+        while link.left_is_more() {
+            link = match link {
+                Link::More(node) => &mut node.left,
+                Link::Empty => unreachable!(),
+            };
+        }
+
+        match mem::replace(link, Link::Empty) {
+            Link::More(node) => {
+                *link = node.right;
+                node.element
+            }
+            Link::Empty => unreachable!("_take_min called on an empty subtree"),
+        }
+    }
+
+    fn _remove(link: &mut Link, _element: i32) -> bool {
+        let link = BST::_locate(link, _element);
+        let node = match link {
+            Link::Empty => return false,
+            Link::More(node) => node,
+        };
+
+        // With two children, pull up the in-order successor in place of the
+        // removed element. Otherwise splice in the only child, if any.
+        if node.left.is_more() && node.right.is_more() {
+            node.element = BST::_take_min(&mut node.right);
+            return true;
+        }
+
+        let child = if node.left.is_more() {
+            mem::replace(&mut node.left, Link::Empty)
+        } else {
+            mem::replace(&mut node.right, Link::Empty)
+        };
+        *link = child;
+
+        true
+    }
+
+    /// Finds the closest element strictly larger (or smaller) than `_element`.
+    fn _neighbour(link: &Link, _element: i32, larger: bool) -> Option<i32> {
+        let mut link = link;
+        let mut best = None;
+
+        while let Link::More(node) = link {
+            let is_candidate = if larger {
+                node.element > _element
+            } else {
+                node.element < _element
+            };
+
+            if is_candidate {
+                best = Some(node.element);
+            }
+
+            // Candidates for a successor only get closer towards smaller keys,
+            // and candidates for a predecessor towards larger ones.
+            link = if is_candidate == larger {
+                &node.left
+            } else {
+                &node.right
+            };
+        }
+
+        best
+    }
+
+    /// Checks every node against the bounds inherited from its ancestors.
+    fn _is_valid(link: &Link, lower: Option<i32>, upper: Option<i32>) -> bool {
+        let mut stack = vec![(link, lower, upper)];
+
+        while let Some((link, lower, upper)) = stack.pop() {
+            if let Link::More(node) = link {
+                if lower.is_some_and(|lower| node.element <= lower)
+                    || upper.is_some_and(|upper| node.element >= upper)
+                {
+                    return false;
+                }
+
+                stack.push((&node.left, lower, Some(node.element)));
+                stack.push((&node.right, Some(node.element), upper));
+            }
+        }
+
+        true
+    }
+
+    /// Removes `element` from the tree, returning whether it was present.
+    pub fn remove(&mut self, element: i32) -> bool {
+        BST::_remove(&mut self.root, element)
+    }
+
+    /// Returns the smallest element, or `None` if the tree is empty.
+    pub fn min(&self) -> Option<i32> {
+        BST::_extreme(&self.root, true)
+    }
+
+    /// Returns the largest element, or `None` if the tree is empty.
+    pub fn max(&self) -> Option<i32> {
+        BST::_extreme(&self.root, false)
+    }
+
+    /// Returns the smallest element greater than `element`, if any.
+    pub fn successor(&self, element: i32) -> Option<i32> {
+        BST::_neighbour(&self.root, element, true)
+    }
+
+    /// Returns the largest element smaller than `element`, if any.
+    pub fn predecessor(&self, element: i32) -> Option<i32> {
+        BST::_neighbour(&self.root, element, false)
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        BST::_len(&self.root)
+    }
+
+    /// Returns whether the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        !self.root.is_more()
+    }
+
+    /// Returns the number of nodes on the longest path from the root to a
+    /// leaf.
+    pub fn height(&self) -> usize {
+        BST::_height(&self.root)
+    }
+
+    /// Checks the binary search tree invariant: every element is larger than
+    /// all elements in its left subtree and smaller than all in its right one.
+    pub fn is_valid(&self) -> bool {
+        BST::_is_valid(&self.root, None, None)
+    }
+
+    /// Iterates over the elements in increasing order.
+    pub fn iter(&self) -> Iter<'_> {
+        let mut iter = Iter { stack: vec![] };
+        iter.push_left(&self.root);
+        iter
+    }
+
+    /// Iterates over the elements visiting each node before its subtrees.
+    pub fn pre_order(&self) -> PreOrder<'_> {
+        PreOrder {
+            stack: self.root.node().into_iter().collect(),
+        }
+    }
+
+    /// Iterates over the elements visiting each node after its subtrees.
+    pub fn post_order(&self) -> PostOrder<'_> {
+        PostOrder {
+            stack: self.root.node().map(|node| (node, false)).into_iter().collect(),
+        }
+    }
+}
+
+/// In-order iterator over a `BST`, see `BST::iter`.
+pub struct Iter<'a> {
+    /// The nodes whose element and right subtree are still to be visited,
+    /// the next one on top.
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iter<'a> {
+    fn push_left(&mut self, link: &'a Link) {
+        let mut link = link;
+
+        while let Link::More(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a i32;
+
+    fn next(&mut self) -> Option<&'a i32> {
+        let node = self.stack.pop()?;
+        self.push_left(&node.right);
+        Some(&node.element)
+    }
+}
+
+/// Pre-order iterator over a `BST`, see `BST::pre_order`.
+pub struct PreOrder<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for PreOrder<'a> {
+    type Item = &'a i32;
+
+    fn next(&mut self) -> Option<&'a i32> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.node());
+        self.stack.extend(node.left.node());
+        Some(&node.element)
+    }
+}
+
+/// Post-order iterator over a `BST`, see `BST::post_order`.
+pub struct PostOrder<'a> {
+    /// Pending nodes, each flagged with whether its subtrees have already been
+    /// pushed above it.
+    stack: Vec<(&'a Node, bool)>,
+}
+
+impl<'a> Iterator for PostOrder<'a> {
+    type Item = &'a i32;
+
+    fn next(&mut self) -> Option<&'a i32> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+
+            if expanded {
+                return Some(&node.element);
+            }
+
+            self.stack.push((node, true));
+            self.stack.extend(node.right.node().map(|node| (node, false)));
+            self.stack.extend(node.left.node().map(|node| (node, false)));
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BST {
+    type Item = &'a i32;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<i32> for BST {
+    fn from_iter<I: IntoIterator<Item = i32>>(iter: I) -> BST {
+        let mut bst = BST::new();
+        bst.extend(iter);
+        bst
+    }
+}
+
+impl Extend<i32> for BST {
+    fn extend<I: IntoIterator<Item = i32>>(&mut self, iter: I) {
+        for element in iter {
+            self.insert(element);
+        }
+    }
+}
+
+impl Default for BST {
+    fn default() -> BST {
+        BST::new()
+    }
+}
+
+impl Drop for BST {
+    /// Frees the nodes one at a time, so that dropping a degenerate tree does
+    /// not recurse once per level.
+    fn drop(&mut self) {
+        let mut stack = vec![mem::replace(&mut self.root, Link::Empty)];
+
+        while let Some(link) = stack.pop() {
+            if let Link::More(mut node) = link {
+                stack.push(mem::replace(&mut node.left, Link::Empty));
+                stack.push(mem::replace(&mut node.right, Link::Empty));
+            }
+        }
+    }
+}
+
+impl Link {
+    fn is_more(&self) -> bool {
+        match self {
+            Link::More(_) => true,
+            Link::Empty => false,
+        }
+    }
+
+    fn node(&self) -> Option<&Node> {
+        match self {
+            Link::More(node) => Some(node),
+            Link::Empty => None,
+        }
+    }
+
+    fn left_is_more(&self) -> bool {
+        match self {
+            Link::More(node) => node.left.is_more(),
+            Link::Empty => false,
+        }
     }
 }
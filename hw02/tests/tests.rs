@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison)]
+
 extern crate hw02;
 
 #[test]
@@ -40,3 +42,281 @@ fn test_find() {
     assert_eq!(bst.find(6), true);
     assert_eq!(bst.find(-6), true);
 }
+
+#[test]
+fn test_zero_is_a_key() {
+    let mut bst = hw02::first::BST::new();
+
+    assert_eq!(bst.is_empty(), true);
+    assert_eq!(bst.find(0), false);
+    assert_eq!(bst.insert(0), true);
+    assert_eq!(bst.find(0), true);
+    assert_eq!(bst.insert(0), false);
+    assert_eq!(bst.is_empty(), false);
+    assert_eq!(bst.remove(0), true);
+    assert_eq!(bst.is_empty(), true);
+    assert_eq!(bst.is_valid(), true);
+}
+
+#[test]
+fn test_remove_leaf() {
+    let mut bst = hw02::first::BST::new();
+
+    bst.insert(10);
+    bst.insert(5);
+    bst.insert(15);
+
+    assert_eq!(bst.remove(5), true);
+    assert_eq!(bst.find(5), false);
+    assert_eq!(bst.remove(5), false);
+    assert_eq!(bst.find(10), true);
+    assert_eq!(bst.find(15), true);
+    assert_eq!(bst.len(), 2);
+    assert_eq!(bst.is_valid(), true);
+}
+
+#[test]
+fn test_remove_one_child() {
+    let mut bst = hw02::first::BST::new();
+
+    for e in &[10, 5, 3, 15, 20] {
+        bst.insert(*e);
+    }
+
+    // 5 only has a left child and 15 only a right one.
+    assert_eq!(bst.remove(5), true);
+    assert_eq!(bst.remove(15), true);
+
+    assert_eq!(bst.find(5), false);
+    assert_eq!(bst.find(15), false);
+    assert_eq!(bst.find(3), true);
+    assert_eq!(bst.find(20), true);
+    assert_eq!(bst.len(), 3);
+    assert_eq!(bst.is_valid(), true);
+}
+
+#[test]
+fn test_remove_two_children() {
+    let mut bst = hw02::first::BST::new();
+
+    for e in &[10, 5, 15, 12, 20, 13] {
+        bst.insert(*e);
+    }
+
+    assert_eq!(bst.remove(10), true);
+    assert_eq!(bst.find(10), false);
+    for e in &[5, 15, 12, 20, 13] {
+        assert_eq!(bst.find(*e), true);
+    }
+    assert_eq!(bst.successor(5), Some(12));
+    assert_eq!(bst.is_valid(), true);
+
+    assert_eq!(bst.remove(15), true);
+    assert_eq!(bst.successor(13), Some(20));
+    assert_eq!(bst.len(), 4);
+    assert_eq!(bst.is_valid(), true);
+}
+
+#[test]
+fn test_remove_root() {
+    let mut bst = hw02::first::BST::new();
+
+    assert_eq!(bst.remove(0), false);
+
+    bst.insert(0);
+    bst.insert(-4);
+    bst.insert(4);
+
+    assert_eq!(bst.remove(0), true);
+    assert_eq!(bst.find(0), false);
+    assert_eq!(bst.find(-4), true);
+    assert_eq!(bst.find(4), true);
+    assert_eq!(bst.len(), 2);
+
+    assert_eq!(bst.remove(4), true);
+    assert_eq!(bst.remove(-4), true);
+    assert_eq!(bst.is_empty(), true);
+}
+
+#[test]
+fn test_remove_matches_oracle() {
+    let mut bst = hw02::first::BST::new();
+    let mut oracle = vec![];
+    let mut state: u32 = 7;
+
+    for _ in 0..2000 {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        let e = (state >> 16) as i32 % 50 - 25;
+        let present = oracle.contains(&e);
+
+        if state & 1 == 0 {
+            assert_eq!(bst.insert(e), !present);
+            if !present {
+                oracle.push(e);
+            }
+        } else {
+            assert_eq!(bst.remove(e), present);
+            oracle.retain(|x| *x != e);
+        }
+
+        assert_eq!(bst.is_valid(), true);
+        assert_eq!(bst.len(), oracle.len());
+        assert_eq!(bst.is_empty(), oracle.is_empty());
+        assert_eq!(bst.min(), oracle.iter().cloned().min());
+        assert_eq!(bst.max(), oracle.iter().cloned().max());
+    }
+}
+
+#[test]
+fn test_min_max() {
+    let mut bst = hw02::first::BST::new();
+
+    assert_eq!(bst.min(), None);
+    assert_eq!(bst.max(), None);
+
+    for e in &[3, -5, 7, -2, 6] {
+        bst.insert(*e);
+    }
+
+    assert_eq!(bst.min(), Some(-5));
+    assert_eq!(bst.max(), Some(7));
+}
+
+#[test]
+fn test_successor_predecessor() {
+    let mut bst = hw02::first::BST::new();
+
+    assert_eq!(bst.successor(0), None);
+    assert_eq!(bst.predecessor(0), None);
+
+    for e in &[0, -10, 4, -3, 8] {
+        bst.insert(*e);
+    }
+
+    assert_eq!(bst.successor(-10), Some(-3));
+    assert_eq!(bst.successor(-4), Some(-3));
+    assert_eq!(bst.successor(0), Some(4));
+    assert_eq!(bst.successor(8), None);
+    assert_eq!(bst.successor(-100), Some(-10));
+
+    assert_eq!(bst.predecessor(4), Some(0));
+    assert_eq!(bst.predecessor(5), Some(4));
+    assert_eq!(bst.predecessor(-10), None);
+    assert_eq!(bst.predecessor(100), Some(8));
+}
+
+#[test]
+fn test_len_height() {
+    let mut bst = hw02::first::BST::new();
+
+    assert_eq!(bst.len(), 0);
+    assert_eq!(bst.height(), 0);
+
+    for e in 1..6 {
+        bst.insert(e);
+    }
+
+    assert_eq!(bst.len(), 5);
+    assert_eq!(bst.height(), 5);
+
+    let mut bst = hw02::first::BST::new();
+
+    for e in &[0, -2, 2, -3, -1, 1, 3] {
+        bst.insert(*e);
+    }
+
+    assert_eq!(bst.len(), 7);
+    assert_eq!(bst.height(), 3);
+    assert_eq!(bst.is_valid(), true);
+}
+
+/// Inserts `n` sorted keys, which degenerates the tree into a single path of
+/// length `n`, and exercises every operation on it.
+fn check_sorted_inserts(n: i32) {
+    let mut bst = hw02::first::BST::new();
+
+    for e in 0..n {
+        assert_eq!(bst.insert(e), true);
+    }
+
+    assert_eq!(bst.find(n - 1), true);
+    assert_eq!(bst.find(n), false);
+    assert_eq!(bst.len(), n as usize);
+    assert_eq!(bst.height(), n as usize);
+    assert_eq!(bst.is_valid(), true);
+    assert_eq!(bst.remove(0), true);
+    assert_eq!(bst.remove(n - 1), true);
+    assert_eq!(bst.min(), Some(1));
+    assert_eq!(bst.max(), Some(n - 2));
+}
+
+/// Runs `check_sorted_inserts` on a thread with a small stack: recursing
+/// once per level of a 20,000-node path needs several megabytes, so this
+/// overflows unless every operation walks the tree iteratively.
+#[test]
+fn test_sorted_inserts() {
+    std::thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(|| check_sorted_inserts(20_000))
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+/// The full million-key run is opt-in, as sorted inserts into an unbalanced
+/// tree take quadratic time: run it with `cargo test -- --ignored`.
+#[test]
+#[ignore = "quadratic in the number of keys, takes very long"]
+fn test_sorted_inserts_stress() {
+    check_sorted_inserts(1_000_000);
+}
+
+#[test]
+fn test_iter_orders() {
+    let mut bst = hw02::first::BST::new();
+
+    assert_eq!(bst.iter().next(), None);
+    assert_eq!(bst.pre_order().next(), None);
+    assert_eq!(bst.post_order().next(), None);
+
+    //        4
+    //      /   \
+    //     2     6
+    //    / \     \
+    //   1   3     7
+    for e in &[4, 2, 6, 1, 3, 7] {
+        bst.insert(*e);
+    }
+
+    assert_eq!(bst.iter().cloned().collect::<Vec<_>>(), vec![1, 2, 3, 4, 6, 7]);
+    assert_eq!(bst.pre_order().cloned().collect::<Vec<_>>(), vec![4, 2, 1, 3, 6, 7]);
+    assert_eq!(bst.post_order().cloned().collect::<Vec<_>>(), vec![1, 3, 2, 7, 6, 4]);
+
+    let mut sum = 0;
+    for e in &bst {
+        sum += *e;
+    }
+    assert_eq!(sum, 23);
+}
+
+#[test]
+fn test_from_iter_extend() {
+    let mut bst: hw02::first::BST = vec![5, -1, 3, 5, 8].into_iter().collect();
+
+    assert_eq!(bst.len(), 4);
+    assert_eq!(bst.iter().cloned().collect::<Vec<_>>(), vec![-1, 3, 5, 8]);
+
+    bst.extend(vec![0, 3, 10]);
+
+    assert_eq!(bst.iter().cloned().collect::<Vec<_>>(), vec![-1, 0, 3, 5, 8, 10]);
+    assert_eq!(bst.is_valid(), true);
+}
+
+#[test]
+fn test_iter_degenerate() {
+    let bst: hw02::first::BST = (0..10_000).rev().collect();
+
+    assert_eq!(bst.iter().cloned().eq(0..10_000), true);
+    assert_eq!(bst.pre_order().cloned().eq((0..10_000).rev()), true);
+    assert_eq!(bst.post_order().cloned().eq(0..10_000), true);
+}
@@ -0,0 +1,237 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[derive(Debug)]
+struct Node<T> {
+    element: T,
+    left: RwLock<Link<T>>,
+    right: RwLock<Link<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(element: T, left: Link<T>, right: Link<T>) -> Arc<Node<T>> {
+        Arc::new(Node {
+            element,
+            left: RwLock::new(left),
+            right: RwLock::new(right),
+        })
+    }
+}
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+// A panic while a lock is held can only come from a comparison or a clone of
+// an element, both done before the tree is modified, so poisoned locks are
+// still consistent.
+
+fn read<T>(lock: &RwLock<Link<T>>) -> RwLockReadGuard<'_, Link<T>> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<Link<T>>) -> RwLockWriteGuard<'_, Link<T>> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+// Every operation walks down from the root with lock coupling: the lock on
+// a child link is taken before the one on its parent is released. Locks are
+// always taken top-down, and left before right, so they cannot deadlock.
+//
+// Holding a link locked also pins the node below it: a node is only ever
+// unlinked by a writer holding the link above it, after it has waited for
+// everyone already inside to move further down.
+
+fn find<T, Q>(guard: RwLockReadGuard<'_, Link<T>>, e: &Q) -> bool
+where
+    T: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let node = match *guard {
+        None => return false,
+        Some(ref node) => node.clone(),
+    };
+    let child = match e.cmp(node.element.borrow()) {
+        Ordering::Less => &node.left,
+        Ordering::Greater => &node.right,
+        Ordering::Equal => return true,
+    };
+
+    let child = read(child);
+    drop(guard);
+    find(child, e)
+}
+
+fn insert<T: Ord>(mut guard: RwLockWriteGuard<'_, Link<T>>, e: T) -> bool {
+    let node = match *guard {
+        None => {
+            *guard = Some(Node::new(e, None, None));
+            return true;
+        }
+        Some(ref node) => node.clone(),
+    };
+    let child = match e.cmp(&node.element) {
+        Ordering::Less => &node.left,
+        Ordering::Greater => &node.right,
+        Ordering::Equal => return false,
+    };
+
+    let child = write(child);
+    drop(guard);
+    insert(child, e)
+}
+
+/// Unlinks the smallest node of the non-empty subtree at `guard` and returns
+/// a copy of its element.
+fn take_min<T: Clone>(mut guard: RwLockWriteGuard<'_, Link<T>>) -> T {
+    let node = guard.clone().expect("take_min called on an empty subtree");
+    let left = write(&node.left);
+
+    if left.is_some() {
+        drop(guard);
+        return take_min(left);
+    }
+
+    let element = node.element.clone();
+    *guard = write(&node.right).take();
+    element
+}
+
+fn remove<T, Q>(mut guard: RwLockWriteGuard<'_, Link<T>>, e: &Q) -> bool
+where
+    T: Borrow<Q> + Clone,
+    Q: Ord + ?Sized,
+{
+    let node = match *guard {
+        None => return false,
+        Some(ref node) => node.clone(),
+    };
+    let child = match e.cmp(node.element.borrow()) {
+        Ordering::Less => &node.left,
+        Ordering::Greater => &node.right,
+        Ordering::Equal => {
+            // Keep the link above locked, so that nobody else can reach the
+            // node while it is replaced.
+            let mut left = write(&node.left);
+            let mut right = write(&node.right);
+
+            *guard = if left.is_some() && right.is_some() {
+                drop(left);
+                let successor = take_min(right);
+                let left = write(&node.left).take();
+                let right = write(&node.right).take();
+                Some(Node::new(successor, left, right))
+            } else {
+                left.take().or_else(|| right.take())
+            };
+            return true;
+        }
+    };
+
+    let child = write(child);
+    drop(guard);
+    remove(child, e)
+}
+
+fn len<T>(guard: RwLockReadGuard<'_, Link<T>>) -> usize {
+    let node = match *guard {
+        None => return 0,
+        Some(ref node) => node.clone(),
+    };
+
+    let left = read(&node.left);
+    let right = read(&node.right);
+    drop(guard);
+    1 + len(left) + len(right)
+}
+
+fn is_valid<T: Ord>(
+    guard: RwLockReadGuard<'_, Link<T>>,
+    lower: Option<&T>,
+    upper: Option<&T>,
+) -> bool {
+    let node = match *guard {
+        None => return true,
+        Some(ref node) => node.clone(),
+    };
+
+    if lower.is_some_and(|lower| node.element <= *lower)
+        || upper.is_some_and(|upper| node.element >= *upper)
+    {
+        return false;
+    }
+
+    let left = read(&node.left);
+    let right = read(&node.right);
+    drop(guard);
+    is_valid(left, lower, Some(&node.element)) && is_valid(right, Some(&node.element), upper)
+}
+
+/// A set stored as a binary search tree that can be shared between threads
+/// and updated concurrently.
+///
+/// Every link between nodes has its own `RwLock`, and operations only hold
+/// the locks of at most a parent and its children at a time, so operations
+/// on different parts of the tree proceed in parallel. The tree does not
+/// rebalance itself.
+#[derive(Debug)]
+pub struct ConcurrentBST<T: Ord> {
+    root: RwLock<Link<T>>,
+}
+
+impl<T: Ord> ConcurrentBST<T> {
+    pub fn new() -> ConcurrentBST<T> {
+        ConcurrentBST {
+            root: RwLock::new(None),
+        }
+    }
+
+    /// Adds `e`, returning whether it was not present yet.
+    pub fn insert(&self, e: T) -> bool {
+        insert(write(&self.root), e)
+    }
+
+    /// Returns whether `e` is in the tree.
+    pub fn find<Q>(&self, e: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        find(read(&self.root), e)
+    }
+
+    /// Returns the number of elements. Elements inserted or removed while
+    /// the tree is being counted may or may not be taken into account.
+    pub fn len(&self) -> usize {
+        len(read(&self.root))
+    }
+
+    /// Returns whether the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        read(&self.root).is_none()
+    }
+
+    /// Checks the binary search tree ordering.
+    pub fn is_valid(&self) -> bool {
+        is_valid(read(&self.root), None, None)
+    }
+}
+
+impl<T: Ord + Clone> ConcurrentBST<T> {
+    /// Removes `e`, returning whether it was present.
+    ///
+    /// Removing an element with two children copies its successor in its
+    /// place, as nodes still in use by other threads cannot be moved.
+    pub fn remove<Q>(&self, e: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        remove(write(&self.root), e)
+    }
+}
+
+impl<T: Ord> Default for ConcurrentBST<T> {
+    fn default() -> ConcurrentBST<T> {
+        ConcurrentBST::new()
+    }
+}
@@ -1,4 +1,8 @@
 #![cfg_attr(feature="clippy", feature(plugin))]
 #![cfg_attr(feature="clippy", plugin(clippy))]
 
+pub mod concurrent;
+pub mod map;
+pub mod persistent;
+pub mod red_black;
 pub mod second;
@@ -0,0 +1,402 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::mem;
+
+use second::{fix, size, take_min, InOrder, Link, Node};
+
+/// An ordered map stored as an AVL-balanced binary search tree of
+/// `(key, value)` pairs, using the same nodes as `second::BST`.
+#[derive(Debug)]
+pub struct BSTMap<K, V> {
+    root: Link<(K, V)>,
+    len: usize,
+}
+
+/// The outcome of inserting a pair into a subtree.
+enum Inserted<V> {
+    /// The key was new; holds the number of smaller keys in the subtree.
+    Added(usize),
+    /// The key was present; holds the value it was mapped to before.
+    Replaced(V),
+}
+
+fn insert<K: Ord, V>(link: &mut Link<(K, V)>, key: K, value: V) -> Inserted<V> {
+    let inserted = match link {
+        None => {
+            *link = Some(Box::new(Node::new((key, value))));
+            return Inserted::Added(0);
+        }
+        Some(node) => match key.cmp(&node.element.0) {
+            Ordering::Less => insert(&mut node.left, key, value),
+            Ordering::Greater => match insert(&mut node.right, key, value) {
+                Inserted::Added(rank) => Inserted::Added(size(&node.left) + 1 + rank),
+                replaced => replaced,
+            },
+            Ordering::Equal => return Inserted::Replaced(mem::replace(&mut node.element.1, value)),
+        },
+    };
+
+    // Rotations keep the order of the pairs, so the rank stays the same.
+    fix(link, true);
+    inserted
+}
+
+/// Returns the pair with `k` smaller keys in the subtree, if any.
+fn select_mut<K, V>(link: &mut Link<(K, V)>, k: usize) -> Option<&mut (K, V)> {
+    let node = link.as_mut()?;
+    let left = size(&node.left);
+
+    match k.cmp(&left) {
+        Ordering::Less => select_mut(&mut node.left, k),
+        Ordering::Equal => Some(&mut node.element),
+        Ordering::Greater => select_mut(&mut node.right, k - left - 1),
+    }
+}
+
+fn get<'a, K, V, Q>(link: &'a Link<(K, V)>, key: &Q) -> Option<&'a (K, V)>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let node = link.as_ref()?;
+
+    match key.cmp(node.element.0.borrow()) {
+        Ordering::Less => get(&node.left, key),
+        Ordering::Greater => get(&node.right, key),
+        Ordering::Equal => Some(&node.element),
+    }
+}
+
+fn get_mut<'a, K, V, Q>(link: &'a mut Link<(K, V)>, key: &Q) -> Option<&'a mut (K, V)>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let node = link.as_mut()?;
+
+    match key.cmp(node.element.0.borrow()) {
+        Ordering::Less => get_mut(&mut node.left, key),
+        Ordering::Greater => get_mut(&mut node.right, key),
+        Ordering::Equal => Some(&mut node.element),
+    }
+}
+
+fn remove<K, V, Q>(link: &mut Link<(K, V)>, key: &Q) -> Option<V>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let node = link.as_mut()?;
+
+    let removed = match key.cmp(node.element.0.borrow()) {
+        Ordering::Less => remove(&mut node.left, key),
+        Ordering::Greater => remove(&mut node.right, key),
+        Ordering::Equal if node.left.is_some() && node.right.is_some() => {
+            // Replace the pair by its in-order successor.
+            let successor = take_min(&mut node.right, true);
+            Some(mem::replace(&mut node.element, successor).1)
+        }
+        Ordering::Equal => {
+            let node = *link.take().unwrap();
+            *link = node.left.or(node.right);
+            return Some(node.element.1);
+        }
+    };
+
+    if removed.is_some() {
+        fix(link, true);
+    }
+    removed
+}
+
+impl<K: Ord, V> BSTMap<K, V> {
+    pub fn new() -> BSTMap<K, V> {
+        BSTMap { root: None, len: 0 }
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the map holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Maps `key` to `value`, returning the value it was mapped to before.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match insert(&mut self.root, key, value) {
+            Inserted::Added(_) => {
+                self.len += 1;
+                None
+            }
+            Inserted::Replaced(old) => Some(old),
+        }
+    }
+
+    /// Returns the value mapped to `key`, if any.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        get(&self.root, key).map(|element| &element.1)
+    }
+
+    /// Returns a mutable reference to the value mapped to `key`, if any.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        get_mut(&mut self.root, key).map(|element| &mut element.1)
+    }
+
+    /// Returns whether `key` is mapped to a value.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        get(&self.root, key).is_some()
+    }
+
+    /// Removes `key`, returning the value it was mapped to.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = remove(&mut self.root, key);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Returns the entry for `key`, to inspect or update it in place.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        if self.contains_key(&key) {
+            Entry::Occupied(OccupiedEntry {
+                element: get_mut(&mut self.root, &key).unwrap(),
+            })
+        } else {
+            Entry::Vacant(VacantEntry { map: self, key })
+        }
+    }
+
+    /// Iterates over the entries in increasing key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: InOrder::new(self.root.as_deref()),
+        }
+    }
+
+    /// Iterates over the entries in increasing key order, with mutable
+    /// references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: InOrder::new(self.root.as_deref_mut()),
+        }
+    }
+}
+
+impl<K: Ord, V> Default for BSTMap<K, V> {
+    fn default() -> BSTMap<K, V> {
+        BSTMap::new()
+    }
+}
+
+/// A view into a single entry of a `BSTMap`, see `BSTMap::entry`.
+pub enum Entry<'a, K: 'a, V: 'a> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+/// An entry whose key is mapped to a value.
+pub struct OccupiedEntry<'a, K: 'a, V: 'a> {
+    element: &'a mut (K, V),
+}
+
+/// An entry whose key is not in the map.
+pub struct VacantEntry<'a, K: 'a, V: 'a> {
+    map: &'a mut BSTMap<K, V>,
+    key: K,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts `default` if the entry is vacant, and returns a mutable
+    /// reference to the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of `default` if the entry is vacant, and returns a
+    /// mutable reference to the value.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Applies `f` to the value if the entry is occupied.
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Entry<'a, K, V> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K: Ord, V: Default> Entry<'a, K, V> {
+    /// Inserts `V::default()` if the entry is vacant, and returns a mutable
+    /// reference to the value.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.element.0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.element.1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.element.1
+    }
+
+    /// Converts the entry into a reference to its value that outlives it.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.element.1
+    }
+
+    /// Replaces the value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(&mut self.element.1, value)
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Maps the key to `value`, and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let map = self.map;
+
+        match insert(&mut map.root, self.key, value) {
+            Inserted::Added(rank) => {
+                map.len += 1;
+                &mut select_mut(&mut map.root, rank)
+                    .expect("inserted pair is in the map")
+                    .1
+            }
+            Inserted::Replaced(_) => unreachable!("vacant entry key is in the map"),
+        }
+    }
+}
+
+/// Borrowing iterator over a `BSTMap`, see `BSTMap::iter`.
+pub struct Iter<'a, K: 'a, V: 'a> {
+    inner: InOrder<&'a Node<(K, V)>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.inner.next().map(|(key, value)| (key, value))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        self.inner.next_back().map(|(key, value)| (key, value))
+    }
+}
+
+/// Iterator with mutable access to the values of a `BSTMap`, see
+/// `BSTMap::iter_mut`.
+pub struct IterMut<'a, K: 'a, V: 'a> {
+    inner: InOrder<&'a mut Node<(K, V)>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
+        self.inner.next().map(|(key, value)| (&*key, value))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<(&'a K, &'a mut V)> {
+        self.inner.next_back().map(|(key, value)| (&*key, value))
+    }
+}
+
+/// Owning iterator over a `BSTMap`, in increasing key order.
+pub struct IntoIter<K, V> {
+    inner: InOrder<Box<Node<(K, V)>>>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.inner.next()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<(K, V)> {
+        self.inner.next_back()
+    }
+}
+
+impl<K: Ord, V> IntoIterator for BSTMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter {
+            inner: InOrder::new(self.root),
+        }
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a BSTMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a mut BSTMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
@@ -0,0 +1,325 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::ops::RangeBounds;
+use std::sync::Arc;
+
+use second::{in_range, InOrder, Split};
+
+/// A node that is never modified once built, so it can be shared by any
+/// number of trees.
+#[derive(Debug)]
+struct Node<T> {
+    element: T,
+    left: Link<T>,
+    right: Link<T>,
+    /// Number of nodes on the longest path from this node down to a leaf.
+    height: usize,
+    /// Number of nodes in the subtree rooted at this node.
+    size: usize,
+}
+
+type Link<T> = Option<Arc<Node<T>>>;
+
+fn height<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+fn create<T>(left: Link<T>, element: T, right: Link<T>) -> Link<T> {
+    Some(Arc::new(Node {
+        height: 1 + height(&left).max(height(&right)),
+        size: 1 + size(&left) + size(&right),
+        element,
+        left,
+        right,
+    }))
+}
+
+/// Like `create`, but with rotations to restore the AVL invariant when the
+/// heights of `left` and `right` differ by two. Both must be AVL trees.
+fn balance<T: Clone>(left: Link<T>, element: T, right: Link<T>) -> Link<T> {
+    let (hl, hr) = (height(&left), height(&right));
+
+    if hl > hr + 1 {
+        let l = left.expect("the higher subtree is not empty");
+
+        if height(&l.left) >= height(&l.right) {
+            create(
+                l.left.clone(),
+                l.element.clone(),
+                create(l.right.clone(), element, right),
+            )
+        } else {
+            let lr = l.right.as_ref().expect("the higher subtree is not empty");
+            create(
+                create(l.left.clone(), l.element.clone(), lr.left.clone()),
+                lr.element.clone(),
+                create(lr.right.clone(), element, right),
+            )
+        }
+    } else if hr > hl + 1 {
+        let r = right.expect("the higher subtree is not empty");
+
+        if height(&r.right) >= height(&r.left) {
+            create(
+                create(left, element, r.left.clone()),
+                r.element.clone(),
+                r.right.clone(),
+            )
+        } else {
+            let rl = r.left.as_ref().expect("the higher subtree is not empty");
+            create(
+                create(left, element, rl.left.clone()),
+                rl.element.clone(),
+                create(rl.right.clone(), r.element.clone(), r.right.clone()),
+            )
+        }
+    } else {
+        create(left, element, right)
+    }
+}
+
+/// Returns a copy of `link` with `e` added, or `None` if it is already
+/// there. Only the nodes on the path to `e` are copied.
+fn insert<T: Ord + Clone>(link: &Link<T>, e: T) -> Option<Link<T>> {
+    let node = match link {
+        None => return Some(create(None, e, None)),
+        Some(node) => node,
+    };
+
+    match e.cmp(&node.element) {
+        Ordering::Less => insert(&node.left, e)
+            .map(|left| balance(left, node.element.clone(), node.right.clone())),
+        Ordering::Greater => insert(&node.right, e)
+            .map(|right| balance(node.left.clone(), node.element.clone(), right)),
+        Ordering::Equal => None,
+    }
+}
+
+/// Returns the smallest element of the non-empty tree at `node`, and a copy
+/// of the tree without it.
+fn remove_min<T: Clone>(node: &Node<T>) -> (T, Link<T>) {
+    match node.left {
+        None => (node.element.clone(), node.right.clone()),
+        Some(ref left) => {
+            let (min, left) = remove_min(left);
+            (min, balance(left, node.element.clone(), node.right.clone()))
+        }
+    }
+}
+
+/// Returns a copy of `link` without `e`, or `None` if it is not there.
+fn remove<T, Q>(link: &Link<T>, e: &Q) -> Option<Link<T>>
+where
+    T: Borrow<Q> + Clone,
+    Q: Ord + ?Sized,
+{
+    let node = link.as_ref()?;
+
+    match e.cmp(node.element.borrow()) {
+        Ordering::Less => remove(&node.left, e)
+            .map(|left| balance(left, node.element.clone(), node.right.clone())),
+        Ordering::Greater => remove(&node.right, e)
+            .map(|right| balance(node.left.clone(), node.element.clone(), right)),
+        Ordering::Equal => Some(match (&node.left, &node.right) {
+            (None, right) => right.clone(),
+            (left, None) => left.clone(),
+            (left, Some(right)) => {
+                let (successor, right) = remove_min(right);
+                balance(left.clone(), successor, right)
+            }
+        }),
+    }
+}
+
+fn is_valid<T: Ord>(link: &Link<T>, lower: Option<&T>, upper: Option<&T>) -> bool {
+    let node = match link {
+        None => return true,
+        Some(node) => node,
+    };
+
+    lower.is_none_or(|lower| node.element > *lower)
+        && upper.is_none_or(|upper| node.element < *upper)
+        && node.height == 1 + height(&node.left).max(height(&node.right))
+        && node.size == 1 + size(&node.left) + size(&node.right)
+        && height(&node.left).abs_diff(height(&node.right)) <= 1
+        && is_valid(&node.left, lower, Some(&node.element))
+        && is_valid(&node.right, Some(&node.element), upper)
+}
+
+/// A persistent set stored as an AVL tree.
+///
+/// Trees are never modified: `insert` and `remove` return a new tree which
+/// shares all the nodes off the changed path with the original one, so both
+/// remain usable. Cloning a tree is O(1), and trees can be shared between
+/// threads.
+#[derive(Debug)]
+pub struct PersistentBST<T: Ord> {
+    root: Link<T>,
+}
+
+impl<T: Ord> PersistentBST<T> {
+    pub fn new() -> PersistentBST<T> {
+        PersistentBST { root: None }
+    }
+
+    /// Returns whether `e` is in the tree.
+    pub fn find<Q>(&self, e: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+
+        while let Some(node) = link {
+            match e.cmp(node.element.borrow()) {
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => link = &node.right,
+                Ordering::Equal => return true,
+            }
+        }
+
+        false
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns whether the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the number of nodes on the longest path from the root to a
+    /// leaf.
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    /// Checks the binary search tree ordering, the stored subtree heights and
+    /// sizes, and that the heights of the two subtrees of every node differ
+    /// by at most one.
+    pub fn is_valid(&self) -> bool {
+        is_valid(&self.root, None, None)
+    }
+
+    /// Iterates over the elements in increasing order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: InOrder::new(self.root.as_deref()),
+        }
+    }
+
+    /// Iterates in increasing order over the elements in `range`, without
+    /// visiting the subtrees outside of it.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Iter {
+            inner: in_range(self.root.as_deref(), &range),
+        }
+    }
+
+    /// Iterates over the elements level by level, starting at the root and
+    /// going left to right within each level.
+    pub fn level_order(&self) -> LevelOrder<'_, T> {
+        LevelOrder {
+            next: self.root.as_deref().into_iter().collect(),
+        }
+    }
+}
+
+impl<T: Ord + Clone> PersistentBST<T> {
+    /// Returns a tree that also holds `e`.
+    pub fn insert(&self, e: T) -> PersistentBST<T> {
+        match insert(&self.root, e) {
+            Some(root) => PersistentBST { root },
+            None => self.clone(),
+        }
+    }
+
+    /// Returns a tree without `e`.
+    pub fn remove<Q>(&self, e: &Q) -> PersistentBST<T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match remove(&self.root, e) {
+            Some(root) => PersistentBST { root },
+            None => self.clone(),
+        }
+    }
+}
+
+impl<T: Ord> Clone for PersistentBST<T> {
+    fn clone(&self) -> PersistentBST<T> {
+        PersistentBST {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T: Ord> Default for PersistentBST<T> {
+    fn default() -> PersistentBST<T> {
+        PersistentBST::new()
+    }
+}
+
+impl<'a, T> Split for &'a Node<T> {
+    type Element = &'a T;
+
+    fn split(self) -> (Option<Self>, &'a T, Option<Self>) {
+        (self.left.as_deref(), &self.element, self.right.as_deref())
+    }
+}
+
+/// Borrowing iterator over a `PersistentBST`, in increasing order.
+pub struct Iter<'a, T: 'a> {
+    inner: InOrder<&'a Node<T>>,
+}
+
+impl<'a, T: Ord> IntoIterator for &'a PersistentBST<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+/// Breadth-first iterator over a `PersistentBST`, see
+/// `PersistentBST::level_order`.
+pub struct LevelOrder<'a, T: 'a> {
+    next: VecDeque<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for LevelOrder<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.pop_front()?;
+        self.next.extend(node.left.as_deref());
+        self.next.extend(node.right.as_deref());
+        Some(&node.element)
+    }
+}
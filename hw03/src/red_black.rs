@@ -0,0 +1,196 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+
+use second::InsertSearch;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Color {
+    Red,
+    Black,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    element: T,
+    left: Link<T>,
+    right: Link<T>,
+    /// Color of the link from the parent to this node.
+    color: Color,
+}
+
+impl<T> Node<T> {
+    fn new(element: T) -> Node<T> {
+        Node {
+            element,
+            left: None,
+            right: None,
+            color: Color::Red,
+        }
+    }
+}
+
+type Link<T> = Option<Box<Node<T>>>;
+
+fn is_red<T>(link: &Link<T>) -> bool {
+    link.as_ref().is_some_and(|node| node.color == Color::Red)
+}
+
+fn rotate_left<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    let mut right = node.right.take().expect("rotating left without a right child");
+
+    node.right = right.left.take();
+    right.color = node.color;
+    node.color = Color::Red;
+    right.left = Some(node);
+    right
+}
+
+fn rotate_right<T>(mut node: Box<Node<T>>) -> Box<Node<T>> {
+    let mut left = node.left.take().expect("rotating right without a left child");
+
+    node.left = left.right.take();
+    left.color = node.color;
+    node.color = Color::Red;
+    left.right = Some(node);
+    left
+}
+
+fn flip_colors<T>(node: &mut Node<T>) {
+    node.color = Color::Red;
+    for child in [&mut node.left, &mut node.right].iter_mut() {
+        if let Some(child) = child.as_mut() {
+            child.color = Color::Black;
+        }
+    }
+}
+
+/// Inserts `e` below `link`, returning the new root of the subtree and
+/// whether `e` was added.
+fn insert<T: Ord>(link: Link<T>, e: T) -> (Box<Node<T>>, bool) {
+    let mut node = match link {
+        None => return (Box::new(Node::new(e)), true),
+        Some(node) => node,
+    };
+
+    let inserted = if e < node.element {
+        let (left, inserted) = insert(node.left.take(), e);
+        node.left = Some(left);
+        inserted
+    } else if e > node.element {
+        let (right, inserted) = insert(node.right.take(), e);
+        node.right = Some(right);
+        inserted
+    } else {
+        false
+    };
+
+    // Restore the left-leaning invariants on the way up.
+    if is_red(&node.right) && !is_red(&node.left) {
+        node = rotate_left(node);
+    }
+    if is_red(&node.left) && node.left.as_ref().is_some_and(|left| is_red(&left.left)) {
+        node = rotate_right(node);
+    }
+    if is_red(&node.left) && is_red(&node.right) {
+        flip_colors(&mut node);
+    }
+
+    (node, inserted)
+}
+
+/// Returns the number of black links on every path from `link` down to a
+/// leaf, or `None` if the tree breaks the ordering or coloring rules.
+fn black_height<T: Ord>(link: &Link<T>, lower: Option<&T>, upper: Option<&T>) -> Option<usize> {
+    let node = match link {
+        None => return Some(0),
+        Some(node) => node,
+    };
+
+    if lower.is_some_and(|lower| node.element <= *lower)
+        || upper.is_some_and(|upper| node.element >= *upper)
+        || is_red(&node.right)
+        || (node.color == Color::Red && is_red(&node.left))
+    {
+        return None;
+    }
+
+    let left = black_height(&node.left, lower, Some(&node.element))?;
+    let right = black_height(&node.right, Some(&node.element), upper)?;
+
+    if left != right {
+        return None;
+    }
+
+    Some(left + (node.color == Color::Black) as usize)
+}
+
+/// A set stored as a left-leaning red-black tree, whose height stays within
+/// 2 log2(n + 1).
+#[derive(Debug)]
+pub struct RBTree<T: Ord> {
+    root: Link<T>,
+}
+
+impl<T: Ord> RBTree<T> {
+    pub fn new() -> RBTree<T> {
+        RBTree { root: None }
+    }
+
+    pub fn insert(&mut self, e: T) -> bool {
+        let (mut root, inserted) = insert(self.root.take(), e);
+        root.color = Color::Black;
+        self.root = Some(root);
+        inserted
+    }
+
+    pub fn find<Q>(&self, e: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+
+        while let Some(node) = link {
+            match e.cmp(node.element.borrow()) {
+                Ordering::Less => link = &node.left,
+                Ordering::Greater => link = &node.right,
+                Ordering::Equal => return true,
+            }
+        }
+
+        false
+    }
+
+    /// Returns the number of nodes on the longest path from the root to a
+    /// leaf.
+    pub fn height(&self) -> usize {
+        fn height<T>(link: &Link<T>) -> usize {
+            link.as_ref()
+                .map_or(0, |node| 1 + height(&node.left).max(height(&node.right)))
+        }
+
+        height(&self.root)
+    }
+
+    /// Checks the binary search tree ordering and the red-black invariants:
+    /// the root is black, red links lean left and never follow each other,
+    /// and every path from the root to a leaf crosses as many black links.
+    pub fn is_valid(&self) -> bool {
+        !is_red(&self.root) && black_height(&self.root, None, None).is_some()
+    }
+}
+
+impl<T: Ord> Default for RBTree<T> {
+    fn default() -> RBTree<T> {
+        RBTree::new()
+    }
+}
+
+impl<T: Ord> InsertSearch<T> for RBTree<T> {
+    fn insert(&mut self, e: T) -> bool {
+        RBTree::insert(self, e)
+    }
+    fn search(&self, e: &T) -> bool {
+        self.find(e)
+    }
+}
@@ -1,197 +1,1012 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::collections::VecDeque;
+use std::ops::{Bound, Deref, RangeBounds};
 
 #[derive(Debug)]
-struct Node<T> {
-    element: T,
-    left: Link<T>,
-    right: Link<T>,
+pub(crate) struct Node<T> {
+    pub(crate) element: T,
+    pub(crate) left: Link<T>,
+    pub(crate) right: Link<T>,
+    /// Number of nodes on the longest path from this node down to a leaf.
+    height: usize,
+    /// Number of nodes in the subtree rooted at this node.
+    size: usize,
 }
 
 impl<T> Node<T> {
     pub fn new(element: T) -> Node<T> {
         Node {
-            element: element,
+            element,
             left: Link::None,
             right: Link::None,
+            height: 1,
+            size: 1,
         }
     }
+
+    /// Recomputes the height and size after the subtrees changed.
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    /// Height of the left subtree minus the height of the right one.
+    fn balance_factor(&self) -> isize {
+        height(&self.left) as isize - height(&self.right) as isize
+    }
 }
 
-type Link<T> = Option<Box<Node<T>>>;
+pub(crate) type Link<T> = Option<Box<Node<T>>>;
 
-trait InsertSearch<T: Default + Ord> {
-    fn insert(&mut self, e: T) -> bool;
-    fn search(&self, e: T) -> bool;
+pub(crate) fn height<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
 }
 
-impl<T: Default + Ord> InsertSearch<T> for Link<T> {
-    fn insert(&mut self, e: T) -> bool {
-        self.as_deref_mut().map(|node| {
-            if node.element == e {
-                false
+pub(crate) fn size<T>(link: &Link<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+/// Replaces the subtree at `link` by its left child, making the old root the
+/// right child of the new one.
+fn rotate_right<T>(link: &mut Link<T>) {
+    let mut node = link.take().expect("rotating an empty subtree");
+    let mut left = node.left.take().expect("rotating right without a left child");
+
+    node.left = left.right.take();
+    node.update();
+    left.right = Some(node);
+    left.update();
+    *link = Some(left);
+}
+
+/// Mirror image of `rotate_right`.
+fn rotate_left<T>(link: &mut Link<T>) {
+    let mut node = link.take().expect("rotating an empty subtree");
+    let mut right = node.right.take().expect("rotating left without a right child");
+
+    node.right = right.left.take();
+    node.update();
+    right.left = Some(node);
+    right.update();
+    *link = Some(right);
+}
+
+/// Recomputes the height and size of the root of `link` after one of its
+/// subtrees changed and, when `balanced`, restores the AVL invariant with at
+/// most two rotations. Both subtrees must already be AVL trees.
+pub(crate) fn fix<T>(link: &mut Link<T>, balanced: bool) {
+    let node = match link.as_mut() {
+        Some(node) => node,
+        None => return,
+    };
+    node.update();
+
+    if !balanced {
+        return;
+    }
+
+    let factor = node.balance_factor();
+    if factor > 1 {
+        if node.left.as_ref().is_some_and(|left| left.balance_factor() < 0) {
+            rotate_left(&mut node.left);
+        }
+        rotate_right(link);
+    } else if factor < -1 {
+        if node.right.as_ref().is_some_and(|right| right.balance_factor() > 0) {
+            rotate_right(&mut node.right);
+        }
+        rotate_left(link);
+    }
+}
+
+fn insert<T: Ord>(link: &mut Link<T>, e: T, balanced: bool) -> bool {
+    let inserted = match link {
+        None => {
+            *link = Some(Box::new(Node::new(e)));
+            return true;
+        }
+        Some(node) => {
+            if e == node.element {
+                return false;
+            } else if e < node.element {
+                insert(&mut node.left, e, balanced)
             } else {
-                if node.element > e {
-                    match node.left {
-                        Link::Some(_) => node.left.insert(e),
-                        _ => {
-                            node.left = Link::Some(Box::new(Node::new(e)));
-                            true
-                        }
-                    }
-                } else {
-                    match node.right {
-                        Link::Some(_) => node.right.insert(e),
-                        _ => {
-                            node.right = Link::Some(Box::new(Node::new(e)));
-                            true
-                        }
-                    }
-                }
+                insert(&mut node.right, e, balanced)
             }
-        }) == Option::Some(true)
+        }
+    };
+
+    if inserted {
+        fix(link, balanced);
     }
-    fn search(&self, e: T) -> bool {
-        self.as_ref().map(|node| {
-            if node.element == e {
-                true
-            } else {
-                if node.element > e {
-                    node.left.search(e)
-                } else {
-                    node.right.search(e)
+    inserted
+}
+
+/// Unlinks the smallest node of the non-empty subtree `link` and returns its
+/// element.
+pub(crate) fn take_min<T>(link: &mut Link<T>, balanced: bool) -> T {
+    let node = link.as_mut().expect("take_min called on an empty subtree");
+
+    if node.left.is_some() {
+        let element = take_min(&mut node.left, balanced);
+        fix(link, balanced);
+        return element;
+    }
+
+    let node = *link.take().unwrap();
+    *link = node.right;
+    node.element
+}
+
+fn find<T, Q>(link: &Link<T>, e: &Q) -> bool
+where
+    T: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let mut link = link;
+
+    while let Some(node) = link {
+        match e.cmp(node.element.borrow()) {
+            Ordering::Less => link = &node.left,
+            Ordering::Greater => link = &node.right,
+            Ordering::Equal => return true,
+        }
+    }
+
+    false
+}
+
+fn remove<T, Q>(link: &mut Link<T>, e: &Q, balanced: bool) -> bool
+where
+    T: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let node = match link {
+        None => return false,
+        Some(node) => node,
+    };
+
+    let ordering = e.cmp(node.element.borrow());
+    if ordering == Ordering::Less {
+        if !remove(&mut node.left, e, balanced) {
+            return false;
+        }
+    } else if ordering == Ordering::Greater {
+        if !remove(&mut node.right, e, balanced) {
+            return false;
+        }
+    } else if node.left.is_some() && node.right.is_some() {
+        // Replace the element by its in-order successor.
+        node.element = take_min(&mut node.right, balanced);
+    } else {
+        let node = *link.take().unwrap();
+        *link = node.left.or(node.right);
+        return true;
+    }
+
+    fix(link, balanced);
+    true
+}
+
+/// Checks the ordering of every node against the bounds inherited from its
+/// ancestors, the stored heights and sizes and, when `balanced`, the AVL
+/// invariant.
+fn is_valid<T: Ord>(link: &Link<T>, lower: Option<&T>, upper: Option<&T>, balanced: bool) -> bool {
+    let node = match link {
+        None => return true,
+        Some(node) => node,
+    };
+
+    lower.is_none_or(|lower| node.element > *lower)
+        && upper.is_none_or(|upper| node.element < *upper)
+        && node.height == 1 + height(&node.left).max(height(&node.right))
+        && node.size == 1 + size(&node.left) + size(&node.right)
+        && (!balanced || node.balance_factor().abs() <= 1)
+        && is_valid(&node.left, lower, Some(&node.element), balanced)
+        && is_valid(&node.right, Some(&node.element), upper, balanced)
+}
+
+/// Builds a perfectly balanced tree out of the next `n` elements of
+/// `sorted`, which must be strictly increasing.
+fn build<T, I: Iterator<Item = T>>(sorted: &mut I, n: usize) -> Link<T> {
+    if n == 0 {
+        return None;
+    }
+
+    let left = build(sorted, n / 2);
+    let element = sorted.next().expect("fewer elements than announced");
+    let right = build(sorted, n - n / 2 - 1);
+    join(left, element, right, false)
+}
+
+/// Returns a tree holding the elements of `left`, then `e`, then those of
+/// `right`, which must all come in increasing order. When `balanced`, `e` is
+/// hung on the spine of the higher tree where both heights match and the
+/// path back to the root is rebalanced, which takes time proportional to the
+/// difference of the heights.
+fn join<T>(left: Link<T>, e: T, right: Link<T>, balanced: bool) -> Link<T> {
+    let mut link = if balanced && height(&left) > height(&right) + 1 {
+        let mut node = left.unwrap();
+        node.right = join(node.right.take(), e, right, true);
+        Some(node)
+    } else if balanced && height(&right) > height(&left) + 1 {
+        let mut node = right.unwrap();
+        node.left = join(left, e, node.left.take(), true);
+        Some(node)
+    } else {
+        let mut node = Box::new(Node::new(e));
+        node.left = left;
+        node.right = right;
+        Some(node)
+    };
+
+    fix(&mut link, balanced);
+    link
+}
+
+/// Like `join`, without an element in the middle.
+fn concat<T>(left: Link<T>, right: Link<T>, balanced: bool) -> Link<T> {
+    let mut right = right;
+
+    if right.is_none() {
+        return left;
+    }
+    let min = take_min(&mut right, balanced);
+    join(left, min, right, balanced)
+}
+
+/// Splits the tree at `link` into the elements smaller than `e`, the one
+/// equal to it if any, and the greater ones.
+fn split<T, Q>(link: Link<T>, e: &Q, balanced: bool) -> (Link<T>, Option<T>, Link<T>)
+where
+    T: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let node = match link {
+        None => return (None, None, None),
+        Some(node) => *node,
+    };
+
+    match e.cmp(node.element.borrow()) {
+        Ordering::Less => {
+            let (left, found, right) = split(node.left, e, balanced);
+            (left, found, join(right, node.element, node.right, balanced))
+        }
+        Ordering::Greater => {
+            let (left, found, right) = split(node.right, e, balanced);
+            (join(node.left, node.element, left, balanced), found, right)
+        }
+        Ordering::Equal => (node.left, Some(node.element), node.right),
+    }
+}
+
+// The set operations below work on AVL trees only. They split the second
+// tree at the root of the first one, recurse on both halves, and join the
+// results back together, which for trees of sizes m <= n takes
+// O(m log(n / m + 1)) time. Unbalanced trees are merged by `merge` instead,
+// as every split could take linear time there.
+
+fn union<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    let (node, b) = match (a, b) {
+        (a, None) => return a,
+        (None, b) => return b,
+        (Some(a), b) => (*a, b),
+    };
+
+    let (left, _, right) = split(b, &node.element, true);
+    let left = union(node.left, left);
+    let right = union(node.right, right);
+    join(left, node.element, right, true)
+}
+
+fn intersection<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    let (node, b) = match (a, b) {
+        (None, _) | (_, None) => return None,
+        (Some(a), b) => (*a, b),
+    };
+
+    let (left, found, right) = split(b, &node.element, true);
+    let left = intersection(node.left, left);
+    let right = intersection(node.right, right);
+    match found {
+        Some(_) => join(left, node.element, right, true),
+        None => concat(left, right, true),
+    }
+}
+
+fn difference<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    let (a, node) = match (a, b) {
+        (None, _) => return None,
+        (a, None) => return a,
+        (a, Some(b)) => (a, *b),
+    };
+
+    let (left, _, right) = split(a, &node.element, true);
+    let left = difference(left, node.left);
+    let right = difference(right, node.right);
+    concat(left, right, true)
+}
+
+fn symmetric_difference<T: Ord>(a: Link<T>, b: Link<T>) -> Link<T> {
+    let (node, b) = match (a, b) {
+        (a, None) => return a,
+        (None, b) => return b,
+        (Some(a), b) => (*a, b),
+    };
+
+    let (left, found, right) = split(b, &node.element, true);
+    let left = symmetric_difference(node.left, left);
+    let right = symmetric_difference(node.right, right);
+    match found {
+        Some(_) => concat(left, right, true),
+        None => join(left, node.element, right, true),
+    }
+}
+
+/// Merges the elements of two sorted iterators, keeping those for which
+/// `keep(in_a, in_b)` holds, into a perfectly balanced tree.
+fn merge<T, A, B, F>(a: A, b: B, keep: F) -> Link<T>
+where
+    T: Ord,
+    A: Iterator<Item = T>,
+    B: Iterator<Item = T>,
+    F: Fn(bool, bool) -> bool,
+{
+    let (mut a, mut b) = (a.peekable(), b.peekable());
+    let mut merged = vec![];
+
+    loop {
+        let (e, in_a, in_b) = match (a.peek(), b.peek()) {
+            (None, None) => break,
+            (Some(_), None) => (a.next().unwrap(), true, false),
+            (None, Some(_)) => (b.next().unwrap(), false, true),
+            (Some(x), Some(y)) => match x.cmp(y) {
+                Ordering::Less => (a.next().unwrap(), true, false),
+                Ordering::Greater => (b.next().unwrap(), false, true),
+                Ordering::Equal => {
+                    b.next();
+                    (a.next().unwrap(), true, true)
                 }
-            }
-        }) == Option::Some(true)
+            },
+        };
+
+        if keep(in_a, in_b) {
+            merged.push(e);
+        }
+    }
+
+    let n = merged.len();
+    build(&mut merged.into_iter(), n)
+}
+
+/// The operations shared by all the tree implementations, so they can be
+/// tested and benchmarked through a single interface.
+pub trait InsertSearch<T: Ord> {
+    /// Adds `e`, returning whether it was not present yet.
+    fn insert(&mut self, e: T) -> bool;
+    /// Returns whether `e` is present.
+    fn search(&self, e: &T) -> bool;
+}
+
+impl<T: Ord> InsertSearch<T> for Link<T> {
+    fn insert(&mut self, e: T) -> bool {
+        insert(self, e, false)
+    }
+    fn search(&self, e: &T) -> bool {
+        find(self, e)
     }
 }
 
 #[derive(Debug)]
-pub struct BST<T: Default + Ord> {
+pub struct BST<T: Ord> {
     root: Link<T>,
+    /// Whether the tree keeps itself AVL-balanced.
+    balanced: bool,
 }
 
-impl<T: Default + Ord> BST<T> {
+impl<T: Ord> BST<T> {
+    /// Creates an empty, unbalanced tree.
     pub fn new() -> BST<T> {
-        BST { root: None }
+        BST {
+            root: None,
+            balanced: false,
+        }
+    }
+
+    /// Creates an empty AVL tree, which rebalances itself on insertion and
+    /// removal so that its height stays within about 1.44 log2(n).
+    pub fn new_avl() -> BST<T> {
+        BST {
+            root: None,
+            balanced: true,
+        }
     }
 
     pub fn insert(&mut self, e: T) -> bool {
-        match self.root {
-            Link::Some(_) => self.root.insert(e),
-            _ => {
-                self.root = Link::Some(Box::new(Node::new(e)));
-                true
+        if self.balanced {
+            insert(&mut self.root, e, true)
+        } else {
+            InsertSearch::insert(&mut self.root, e)
+        }
+    }
+
+    /// Returns whether `e` is in the tree. Any borrowed form of the element
+    /// type can be looked up, e.g. `&str` in a `BST<String>`.
+    pub fn find<Q>(&self, e: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        find(&self.root, e)
+    }
+
+    /// Removes `e` from the tree, returning whether it was present.
+    pub fn remove<Q>(&mut self, e: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        remove(&mut self.root, e, self.balanced)
+    }
+
+    /// Returns the number of nodes on the longest path from the root to a
+    /// leaf.
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns whether the tree holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Returns the `k`-th smallest element, counting from 0, or `None` if
+    /// there are at most `k` elements.
+    pub fn select(&self, k: usize) -> Option<&T> {
+        let mut link = &self.root;
+        let mut k = k;
+
+        while let Some(node) = link {
+            let left = size(&node.left);
+
+            match k.cmp(&left) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.element),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    link = &node.right;
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the number of elements smaller than `e`, which is also the
+    /// index `e` has or would have in sorted order.
+    pub fn rank<Q>(&self, e: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+        let mut rank = 0;
+
+        while let Some(node) = link {
+            match e.cmp(node.element.borrow()) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return rank + size(&node.left),
+                Ordering::Greater => {
+                    rank += size(&node.left) + 1;
+                    link = &node.right;
+                }
             }
         }
+
+        rank
+    }
+
+    /// Checks the binary search tree ordering, the stored subtree heights and
+    /// sizes and, for AVL trees, that the heights of the two subtrees of every
+    /// node differ by at most one.
+    pub fn is_valid(&self) -> bool {
+        is_valid(&self.root, None, None, self.balanced)
+    }
+
+    /// Iterates over the elements in increasing order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: InOrder::new(self.root.as_deref()),
+        }
+    }
+
+    /// Iterates over the elements in increasing order, allowing to modify
+    /// them.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            inner: InOrder::new(self.root.as_deref_mut()),
+        }
+    }
+
+    /// Iterates in increasing order over the elements in `range`, without
+    /// visiting the subtrees outside of it.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range {
+            inner: in_range(self.root.as_deref(), &range),
+        }
     }
 
-    pub fn find(&self, e: T) -> bool {
-        match self.root {
-            Link::Some(_) => self.root.search(e),
-            _ => false,
+    /// Like `range`, but allowing to modify the elements.
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        RangeMut {
+            inner: in_range(self.root.as_deref_mut(), &range),
+        }
+    }
+
+    /// Iterates over the elements level by level, starting at the root and
+    /// going left to right within each level.
+    pub fn level_order(&self) -> LevelOrder<'_, T> {
+        LevelOrder {
+            next: VecDeque::from(vec![self.root.as_deref()]),
         }
     }
 }
 
-pub struct IntoIter<T: Copy> {
-    next: VecDeque<Link<T>>,
+impl<T: Ord> BST<T> {
+    /// Returns the root of the tree, first rebuilding it as a perfectly
+    /// balanced tree in linear time if an AVL tree is wanted and it is not
+    /// one.
+    fn into_root(self, balanced: bool) -> Link<T> {
+        if balanced && !self.balanced {
+            let n = self.len();
+            build(&mut self.into_iter(), n)
+        } else {
+            self.root
+        }
+    }
+
+    /// Combines `self` and `other` with `avl` if `self` is an AVL tree, and
+    /// otherwise by merging their elements in sorted order, keeping those for
+    /// which `keep(in_self, in_other)` holds.
+    fn combine<F>(self, other: BST<T>, avl: fn(Link<T>, Link<T>) -> Link<T>, keep: F) -> BST<T>
+    where
+        F: Fn(bool, bool) -> bool,
+    {
+        let balanced = self.balanced;
+        let root = if balanced {
+            avl(self.root, other.into_root(true))
+        } else {
+            merge(self.into_iter(), other.into_iter(), keep)
+        };
+
+        BST { root, balanced }
+    }
+
+    /// Returns the tree holding the elements in `self`, in `other` or in
+    /// both. The result is an AVL tree if `self` is one.
+    ///
+    /// Like the other set operations, this never inserts the elements one by
+    /// one, and takes time linear in the sizes of the two trees at most: AVL
+    /// trees are split and joined, unbalanced ones are merged in sorted order
+    /// into a perfectly balanced tree.
+    pub fn union(self, other: BST<T>) -> BST<T> {
+        self.combine(other, union, |in_a, in_b| in_a || in_b)
+    }
+
+    /// Returns the tree holding the elements in both `self` and `other`.
+    pub fn intersection(self, other: BST<T>) -> BST<T> {
+        self.combine(other, intersection, |in_a, in_b| in_a && in_b)
+    }
+
+    /// Returns the tree holding the elements in `self` but not in `other`.
+    pub fn difference(self, other: BST<T>) -> BST<T> {
+        self.combine(other, difference, |in_a, in_b| in_a && !in_b)
+    }
+
+    /// Returns the tree holding the elements in exactly one of `self` and
+    /// `other`.
+    pub fn symmetric_difference(self, other: BST<T>) -> BST<T> {
+        self.combine(other, symmetric_difference, |in_a, in_b| in_a != in_b)
+    }
+
+    /// Splits the tree into the elements smaller than `e` and the others,
+    /// in time proportional to its height.
+    pub fn split_at<Q>(self, e: &Q) -> (BST<T>, BST<T>)
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let balanced = self.balanced;
+        let (left, found, right) = split(self.into_root(balanced), e, balanced);
+        let right = match found {
+            Some(found) => join(None, found, right, balanced),
+            None => right,
+        };
+
+        (
+            BST {
+                root: left,
+                balanced,
+            },
+            BST {
+                root: right,
+                balanced,
+            },
+        )
+    }
+
+    /// Returns the tree holding the elements of `self` followed by those of
+    /// `other`, in time proportional to the heights of the two trees.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some element of `self` is not smaller than all the elements
+    /// of `other`.
+    pub fn join(self, other: BST<T>) -> BST<T> {
+        if let (Some(last), Some(first)) = (self.iter().next_back(), other.iter().next()) {
+            assert!(last < first, "joining trees whose elements overlap");
+        }
+
+        let balanced = self.balanced;
+        let other = other.into_root(balanced);
+        BST {
+            root: concat(self.into_root(balanced), other, balanced),
+            balanced,
+        }
+    }
 }
 
-impl<T: Copy> Iterator for IntoIter<T> {
-    type Item = T;
-    fn next(&mut self) -> Option<Self::Item> {
-        let next = self.next.pop_front();
+impl<T: Ord> InsertSearch<T> for BST<T> {
+    fn insert(&mut self, e: T) -> bool {
+        BST::insert(self, e)
+    }
+    fn search(&self, e: &T) -> bool {
+        self.find(e)
+    }
+}
+
+impl<T: Ord> Default for BST<T> {
+    fn default() -> BST<T> {
+        BST::new()
+    }
+}
+
+/// A node handle that in-order iterators can take apart.
+pub(crate) trait Split: Sized {
+    type Element;
+
+    /// Returns the left subtree, the element and the right subtree.
+    fn split(self) -> (Option<Self>, Self::Element, Option<Self>);
+}
+
+impl<'a, T> Split for &'a Node<T> {
+    type Element = &'a T;
+
+    fn split(self) -> (Option<Self>, &'a T, Option<Self>) {
+        (self.left.as_deref(), &self.element, self.right.as_deref())
+    }
+}
+
+impl<'a, T> Split for &'a mut Node<T> {
+    type Element = &'a mut T;
+
+    fn split(self) -> (Option<Self>, &'a mut T, Option<Self>) {
+        let Node {
+            element,
+            left,
+            right,
+            ..
+        } = self;
+        (left.as_deref_mut(), element, right.as_deref_mut())
+    }
+}
+
+impl<T> Split for Box<Node<T>> {
+    type Element = T;
+
+    fn split(self) -> (Option<Self>, T, Option<Self>) {
+        let node = *self;
+        (node.left, node.element, node.right)
+    }
+}
+
+enum Piece<N: Split> {
+    Element(N::Element),
+    Tree(N),
+}
+
+/// The in-order traversal shared by `Iter`, `IterMut` and `IntoIter`, and by
+/// the iterators of `BSTMap` and `PersistentBST`.
+///
+/// The elements still to be visited are kept, in order, as a sequence of
+/// single elements and whole subtrees. Subtrees are only taken apart once
+/// they reach either end, so both ends can be consumed independently.
+pub(crate) struct InOrder<N: Split> {
+    pieces: VecDeque<Piece<N>>,
+}
 
-        match next {
-            Some(link) => match link {
-                Some(node) => {
-                    if node.left.is_some() {
-                        self.next.push_back(node.left);
+impl<N: Split> InOrder<N> {
+    pub(crate) fn new(root: Option<N>) -> InOrder<N> {
+        InOrder {
+            pieces: root.into_iter().map(Piece::Tree).collect(),
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> Option<N::Element> {
+        loop {
+            match self.pieces.pop_front()? {
+                Piece::Element(element) => return Some(element),
+                Piece::Tree(node) => {
+                    let (left, element, right) = node.split();
+                    if let Some(right) = right {
+                        self.pieces.push_front(Piece::Tree(right));
                     }
-                    if node.right.is_some() {
-                        self.next.push_back(node.right);
+                    self.pieces.push_front(Piece::Element(element));
+                    if let Some(left) = left {
+                        self.pieces.push_front(Piece::Tree(left));
                     }
-                    let value = node.element;
-                    Some(value)
                 }
-                _ => None,
-            },
-            _ => None,
+            }
+        }
+    }
+
+    pub(crate) fn next_back(&mut self) -> Option<N::Element> {
+        loop {
+            match self.pieces.pop_back()? {
+                Piece::Element(element) => return Some(element),
+                Piece::Tree(node) => {
+                    let (left, element, right) = node.split();
+                    if let Some(left) = left {
+                        self.pieces.push_back(Piece::Tree(left));
+                    }
+                    self.pieces.push_back(Piece::Element(element));
+                    if let Some(right) = right {
+                        self.pieces.push_back(Piece::Tree(right));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn above_lower<Q: Ord + ?Sized>(e: &Q, bound: Bound<&Q>) -> bool {
+    match bound {
+        Bound::Included(lower) => e >= lower,
+        Bound::Excluded(lower) => e > lower,
+        Bound::Unbounded => true,
+    }
+}
+
+fn below_upper<Q: Ord + ?Sized>(e: &Q, bound: Bound<&Q>) -> bool {
+    match bound {
+        Bound::Included(upper) => e <= upper,
+        Bound::Excluded(upper) => e < upper,
+        Bound::Unbounded => true,
+    }
+}
+
+/// Returns an in-order traversal of the elements of the tree at `root` that
+/// lie in `range`, after a single descent through it.
+pub(crate) fn in_range<T, Q, N, R>(root: Option<N>, range: &R) -> InOrder<N>
+where
+    T: Borrow<Q>,
+    Q: Ord + ?Sized,
+    N: Split,
+    N::Element: Deref<Target = T>,
+    R: RangeBounds<Q>,
+{
+    let (lower, upper) = (range.start_bound(), range.end_bound());
+    let mut pieces = VecDeque::new();
+
+    // Find the topmost node in the range; the paths to both bounds go
+    // through it.
+    let mut link = root;
+    while let Some(node) = link {
+        let (left, element, right) = node.split();
+
+        if !above_lower((*element).borrow(), lower) {
+            link = right;
+        } else if !below_upper((*element).borrow(), upper) {
+            link = left;
+        } else {
+            pieces.push_back(Piece::Element(element));
+
+            // Everything in the left subtree is below the upper bound; keep
+            // the nodes above the lower one, each with its right subtree.
+            let mut link = left;
+            while let Some(node) = link {
+                let (left, element, right) = node.split();
+
+                if above_lower((*element).borrow(), lower) {
+                    if let Some(right) = right {
+                        pieces.push_front(Piece::Tree(right));
+                    }
+                    pieces.push_front(Piece::Element(element));
+                    link = left;
+                } else {
+                    link = right;
+                }
+            }
+
+            // And symmetrically for the right subtree.
+            let mut link = right;
+            while let Some(node) = link {
+                let (left, element, right) = node.split();
+
+                if below_upper((*element).borrow(), upper) {
+                    if let Some(left) = left {
+                        pieces.push_back(Piece::Tree(left));
+                    }
+                    pieces.push_back(Piece::Element(element));
+                    link = right;
+                } else {
+                    link = left;
+                }
+            }
+
+            break;
         }
     }
+
+    InOrder { pieces }
+}
+
+/// Owning iterator over a `BST`, in increasing order.
+pub struct IntoIter<T> {
+    inner: InOrder<Box<Node<T>>>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
 }
 
-impl<T: Default + Copy + Ord> IntoIterator for BST<T> {
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T: Ord> IntoIterator for BST<T> {
     type Item = T;
     type IntoIter = IntoIter<Self::Item>;
 
-    fn into_iter(self) -> IntoIter<T> {
+    fn into_iter(mut self) -> IntoIter<T> {
         IntoIter {
-            next: VecDeque::from(vec![self.root]),
+            inner: InOrder::new(self.root.take()),
         }
     }
 }
 
+/// Borrowing iterator over a `BST`, in increasing order.
 pub struct Iter<'a, T: 'a> {
-    next: VecDeque<Option<&'a Node<T>>>,
+    inner: InOrder<&'a Node<T>>,
 }
 
-impl<'a, T: Default + Copy + Ord> IntoIterator for &'a BST<T> {
+impl<'a, T: Ord> IntoIterator for &'a BST<T> {
     type Item = &'a T;
     type IntoIter = Iter<'a, T>;
     fn into_iter(self) -> Self::IntoIter {
-        Iter {
-            next: VecDeque::from(vec![self.root.as_ref().map(|link| &**link)]),
-        }
+        self.iter()
     }
 }
 
-impl<'a, T: Default + Copy + Ord> Iterator for Iter<'a, T> {
+impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
     fn next(&mut self) -> Option<Self::Item> {
-        self.next.pop_front().map(|link| {
-            link.map(|node| {
-                if node.left.is_some() {
-                    self.next.push_back(node.left.as_ref().map(|node| &**node));
-                }
-                if node.right.is_some() {
-                    self.next.push_back(node.right.as_ref().map(|node| &**node));
-                }
+        self.inner.next()
+    }
+}
 
-                &node.element
-            })
-        }).flatten()
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
     }
 }
 
+/// Mutably borrowing iterator over a `BST`, in increasing order.
+///
+/// Changing the elements in a way that affects their ordering leaves the
+/// tree invalid.
 pub struct IterMut<'a, T: 'a> {
-    next: VecDeque<Option<&'a mut Node<T>>>,
+    inner: InOrder<&'a mut Node<T>>,
 }
 
-impl<'a, T: Default + Copy + Ord> IntoIterator for &'a mut BST<T> {
+impl<'a, T: Ord> IntoIterator for &'a mut BST<T> {
     type Item = &'a mut T;
     type IntoIter = IterMut<'a, T>;
     fn into_iter(self) -> Self::IntoIter {
-        IterMut {
-            next: VecDeque::from(vec![self.root.as_mut().map(|link| &mut **link)]),
-        }
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+/// Iterator over the elements of a `BST` within a range, see `BST::range`.
+pub struct Range<'a, T: 'a> {
+    inner: InOrder<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
     }
 }
 
-impl<'a, T: Default + Copy + Ord> Iterator for IterMut<'a, T> {
+impl<'a, T> DoubleEndedIterator for Range<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+/// Mutable iterator over the elements of a `BST` within a range, see
+/// `BST::range_mut`.
+pub struct RangeMut<'a, T: 'a> {
+    inner: InOrder<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for RangeMut<'a, T> {
     type Item = &'a mut T;
     fn next(&mut self) -> Option<Self::Item> {
-        self.next.pop_front().map(|link| {
+        self.inner.next()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for RangeMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+/// Breadth-first iterator over a `BST`, see `BST::level_order`.
+pub struct LevelOrder<'a, T: 'a> {
+    next: VecDeque<Option<&'a Node<T>>>,
+}
+
+impl<'a, T> Iterator for LevelOrder<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.pop_front().and_then(|link| {
             link.map(|node| {
                 if node.left.is_some() {
-                    self.next.push_back(node.left.as_mut().map(|node| &mut **node));
+                    self.next.push_back(node.left.as_deref());
                 }
                 if node.right.is_some() {
-                    self.next.push_back(node.right.as_mut().map(|node| &mut **node));
+                    self.next.push_back(node.right.as_deref());
                 }
 
-                &mut node.element
+                &node.element
             })
-        }).flatten()
+        })
     }
 }
@@ -1,5 +1,26 @@
+#![allow(clippy::bool_assert_comparison)]
+
 extern crate hw03;
 
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+
+use hw03::concurrent::ConcurrentBST;
+use hw03::map::{BSTMap, Entry};
+use hw03::persistent::PersistentBST;
+use hw03::red_black::RBTree;
+use hw03::second::InsertSearch;
+
+/// Advances the linear congruential generator `state` and returns a key in
+/// `0..modulo` taken from its high bits.
+fn next_key(state: &mut u32, modulo: i32) -> i32 {
+    *state = state.wrapping_mul(1103515245).wrapping_add(12345);
+    (*state >> 16) as i32 % modulo
+}
+
 #[test]
 fn test_insert() {
     let mut bst = hw03::second::BST::new();
@@ -21,8 +42,8 @@ fn test_insert() {
 fn test_find() {
     let mut bst = hw03::second::BST::new();
 
-    assert_eq!(bst.find(1), false);
-    assert_eq!(bst.find(-1), false);
+    assert_eq!(bst.find(&1), false);
+    assert_eq!(bst.find(&-1), false);
 
     bst.insert(5);
     bst.insert(-5);
@@ -31,25 +52,25 @@ fn test_find() {
     bst.insert(6);
     bst.insert(-6);
 
-    assert_eq!(bst.find(1), false);
-    assert_eq!(bst.find(-1), false);
-    assert_eq!(bst.find(2), true);
-    assert_eq!(bst.find(-2), true);
-    assert_eq!(bst.find(5), true);
-    assert_eq!(bst.find(-5), true);
-    assert_eq!(bst.find(6), true);
-    assert_eq!(bst.find(-6), true);
+    assert_eq!(bst.find(&1), false);
+    assert_eq!(bst.find(&-1), false);
+    assert_eq!(bst.find(&2), true);
+    assert_eq!(bst.find(&-2), true);
+    assert_eq!(bst.find(&5), true);
+    assert_eq!(bst.find(&-5), true);
+    assert_eq!(bst.find(&6), true);
+    assert_eq!(bst.find(&-6), true);
 }
 
 #[test]
 fn test_iterator() {
     let mut bst = hw03::second::BST::new();
 
-    assert_eq!(bst.find(1), false);
-    assert_eq!(bst.find(-1), false);
+    assert_eq!(bst.find(&1), false);
+    assert_eq!(bst.find(&-1), false);
 
     let elements = vec![5,-5,2,-2,6,-6];
-    let expected = vec![5,-5,6,-6,2,-2];
+    let expected = vec![-6,-5,-2,2,5,6];
 
     for e in &elements {
         bst.insert(*e);
@@ -65,11 +86,11 @@ fn test_iterator() {
 fn test_iter() {
     let mut bst = hw03::second::BST::new();
 
-    assert_eq!(bst.find(1), false);
-    assert_eq!(bst.find(-1), false);
+    assert_eq!(bst.find(&1), false);
+    assert_eq!(bst.find(&-1), false);
 
     let elements = vec![5,-5,2,-2,6,-6];
-    let expected = vec![5,-5,6,-6,2,-2];
+    let expected = vec![-6,-5,-2,2,5,6];
 
     for e in &elements {
         bst.insert(*e);
@@ -88,11 +109,11 @@ fn test_iter() {
 fn test_mut_iter() {
     let mut bst = hw03::second::BST::new();
 
-    assert_eq!(bst.find(1), false);
-    assert_eq!(bst.find(-1), false);
+    assert_eq!(bst.find(&1), false);
+    assert_eq!(bst.find(&-1), false);
 
     let elements = vec![5,-5,2,-2,6,-6];
-    let expected = vec![5,-5,6,-6,2,-2];
+    let expected = vec![-6,-5,-2,2,5,6];
 
     for e in &elements {
         bst.insert(*e);
@@ -106,3 +127,867 @@ fn test_mut_iter() {
     
     assert_eq!(expected, result);
 }
+
+#[test]
+fn test_level_order() {
+    let mut bst = hw03::second::BST::new();
+
+    let elements = vec![5,-5,2,-2,6,-6];
+    let expected = vec![5,-5,6,-6,2,-2];
+
+    for e in &elements {
+        bst.insert(*e);
+    }
+
+    let result: Vec<i32> = bst.level_order().cloned().collect();
+
+    assert_eq!(expected, result);
+}
+
+#[test]
+fn test_iter_rev() {
+    let mut bst = hw03::second::BST::new_avl();
+
+    for e in &[5, -5, 2, -2, 6, -6, 0] {
+        bst.insert(*e);
+    }
+
+    assert_eq!(bst.iter().rev().cloned().collect::<Vec<_>>(), vec![6, 5, 2, 0, -2, -5, -6]);
+
+    for e in bst.iter_mut().rev().take(2) {
+        *e += 100;
+    }
+    assert_eq!(bst.iter().cloned().collect::<Vec<_>>(), vec![-6, -5, -2, 0, 2, 105, 106]);
+
+    assert_eq!(bst.into_iter().rev().collect::<Vec<_>>(), vec![106, 105, 2, 0, -2, -5, -6]);
+}
+
+#[test]
+fn test_iter_both_ends() {
+    let mut bst = hw03::second::BST::new();
+    let mut state: u32 = 9;
+
+    for _ in 0..500 {
+        bst.insert(next_key(&mut state, 1000));
+    }
+
+    let sorted: Vec<i32> = bst.iter().cloned().collect();
+    assert!(sorted.windows(2).all(|w| w[0] < w[1]));
+
+    // Alternate between the two ends according to the bits of `state`.
+    let mut iter = bst.iter();
+    let (mut front, mut back) = (vec![], vec![]);
+    loop {
+        let next = if next_key(&mut state, 2) == 0 {
+            iter.next().map(|e| front.push(*e))
+        } else {
+            iter.next_back().map(|e| back.push(*e))
+        };
+        if next.is_none() {
+            break;
+        }
+    }
+
+    front.extend(back.into_iter().rev());
+    assert_eq!(front, sorted);
+}
+
+#[test]
+fn test_remove() {
+    let mut bst = hw03::second::BST::new();
+
+    assert_eq!(bst.remove(&1), false);
+
+    for e in &[10, 5, 15, 3, 12, 20, 13] {
+        bst.insert(*e);
+    }
+
+    // 3 is a leaf, 12 has one child and 10 has two.
+    assert_eq!(bst.remove(&3), true);
+    assert_eq!(bst.remove(&12), true);
+    assert_eq!(bst.remove(&10), true);
+    assert_eq!(bst.remove(&10), false);
+
+    for e in &[3, 10, 12] {
+        assert_eq!(bst.find(e), false);
+    }
+    for e in &[5, 13, 15, 20] {
+        assert_eq!(bst.find(e), true);
+    }
+    assert_eq!(bst.is_valid(), true);
+}
+
+#[test]
+fn test_avl_sorted_inserts() {
+    let mut bst = hw03::second::BST::new_avl();
+
+    for e in 0..1023 {
+        assert_eq!(bst.insert(e), true);
+    }
+
+    // Sorted input fills a perfect tree of depth 10.
+    assert_eq!(bst.height(), 10);
+    assert_eq!(bst.is_valid(), true);
+    assert_eq!(bst.find(&0), true);
+    assert_eq!(bst.find(&1022), true);
+    assert_eq!(bst.find(&1023), false);
+
+    for e in 0..1000 {
+        assert_eq!(bst.remove(&e), true);
+        assert_eq!(bst.is_valid(), true);
+    }
+    assert_eq!(bst.height(), 5);
+}
+
+#[test]
+fn test_avl_rotations() {
+    // Each order needs a different single or double rotation and ends up
+    // with 2 at the root.
+    for order in &[[1, 2, 3], [3, 2, 1], [1, 3, 2], [3, 1, 2]] {
+        let mut bst = hw03::second::BST::new_avl();
+
+        for e in order {
+            bst.insert(*e);
+        }
+
+        assert_eq!(bst.height(), 2);
+        assert_eq!(bst.is_valid(), true);
+        assert_eq!(bst.level_order().next(), Some(&2));
+    }
+}
+
+#[test]
+fn test_avl_matches_oracle() {
+    let mut bst = hw03::second::BST::new_avl();
+    let mut oracle = vec![];
+    let mut state: u32 = 42;
+
+    for _ in 0..5000 {
+        let e = next_key(&mut state, 200);
+        let present = oracle.contains(&e);
+
+        if state & 3 != 0 {
+            assert_eq!(bst.insert(e), !present);
+            if !present {
+                oracle.push(e);
+            }
+        } else {
+            assert_eq!(bst.remove(&e), present);
+            oracle.retain(|x| *x != e);
+        }
+
+        assert_eq!(bst.is_valid(), true);
+    }
+
+    for e in 0..200 {
+        assert_eq!(bst.find(&e), oracle.contains(&e));
+    }
+
+    // An AVL tree with n nodes is at most about 1.44 log2(n) high.
+    let bound = 1.45 * ((oracle.len() + 2) as f64).log2();
+    assert!((bst.height() as f64) <= bound);
+}
+
+/// Runs the same insert/search scenario against any tree implementation.
+fn check_insert_search<S: InsertSearch<i32>>(mut set: S) {
+    let mut state: u32 = 3;
+    let mut oracle = vec![];
+
+    for e in &[1, 2, 3] {
+        assert_eq!(set.insert(*e), true);
+        assert_eq!(set.insert(*e), false);
+    }
+    oracle.extend_from_slice(&[1, 2, 3]);
+
+    for _ in 0..2000 {
+        let e = next_key(&mut state, 500) - 250;
+
+        assert_eq!(set.insert(e), !oracle.contains(&e));
+        if !oracle.contains(&e) {
+            oracle.push(e);
+        }
+    }
+
+    for e in -300..300 {
+        assert_eq!(set.search(&e), oracle.contains(&e));
+    }
+}
+
+#[test]
+fn test_insert_search_implementations() {
+    check_insert_search(hw03::second::BST::new());
+    check_insert_search(hw03::second::BST::new_avl());
+    check_insert_search(RBTree::new());
+}
+
+#[test]
+fn test_red_black_sorted_inserts() {
+    let mut tree = RBTree::new();
+
+    for e in 0..10_000 {
+        assert_eq!(tree.insert(e), true);
+    }
+
+    assert_eq!(tree.is_valid(), true);
+    assert_eq!(tree.find(&0), true);
+    assert_eq!(tree.find(&9_999), true);
+    assert_eq!(tree.find(&10_000), false);
+
+    // A red-black tree with n nodes is at most 2 log2(n + 1) high.
+    assert!(tree.height() <= 2 * 14);
+}
+
+#[test]
+fn test_red_black_random_inserts() {
+    let mut tree = RBTree::new();
+    let mut state: u32 = 11;
+
+    for _ in 0..3000 {
+        tree.insert(next_key(&mut state, 1000));
+        assert_eq!(tree.is_valid(), true);
+    }
+}
+
+#[test]
+fn test_map_insert_get_remove() {
+    let mut map = BSTMap::new();
+
+    assert_eq!(map.is_empty(), true);
+    assert_eq!(map.get(&1), None);
+    assert_eq!(map.insert(1, "one"), None);
+    assert_eq!(map.insert(2, "two"), None);
+    assert_eq!(map.insert(1, "uno"), Some("one"));
+    assert_eq!(map.len(), 2);
+
+    assert_eq!(map.get(&1), Some(&"uno"));
+    assert_eq!(map.contains_key(&2), true);
+    assert_eq!(map.contains_key(&3), false);
+
+    *map.get_mut(&2).unwrap() = "dos";
+    assert_eq!(map.get(&2), Some(&"dos"));
+
+    assert_eq!(map.remove(&1), Some("uno"));
+    assert_eq!(map.remove(&1), None);
+    assert_eq!(map.len(), 1);
+}
+
+#[test]
+fn test_map_borrowed_keys() {
+    let mut map = BSTMap::new();
+
+    map.insert("apple".to_string(), 3);
+    map.insert("pear".to_string(), 5);
+
+    assert_eq!(map.get("apple"), Some(&3));
+    assert_eq!(map.contains_key("plum"), false);
+    assert_eq!(map.remove("pear"), Some(5));
+}
+
+#[test]
+fn test_map_entry() {
+    let mut map = BSTMap::new();
+
+    for word in "a b a c b a".split(' ') {
+        *map.entry(word).or_insert(0) += 1;
+    }
+
+    assert_eq!(map.get("a"), Some(&3));
+    assert_eq!(map.get("b"), Some(&2));
+    assert_eq!(map.get("c"), Some(&1));
+
+    map.entry("a").and_modify(|count| *count *= 10).or_default();
+    map.entry("d").and_modify(|count| *count *= 10).or_default();
+    assert_eq!(map.get("a"), Some(&30));
+    assert_eq!(map.get("d"), Some(&0));
+
+    match map.entry("b") {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(*entry.key(), "b");
+            assert_eq!(entry.insert(7), 2);
+        }
+        Entry::Vacant(_) => panic!("b is in the map"),
+    }
+    match map.entry("e") {
+        Entry::Occupied(_) => panic!("e is not in the map"),
+        Entry::Vacant(entry) => *entry.insert(1) += 1,
+    }
+
+    assert_eq!(map.get("b"), Some(&7));
+    assert_eq!(map.get("e"), Some(&2));
+    assert_eq!(map.len(), 5);
+}
+
+#[test]
+fn test_map_iterators() {
+    let mut map = BSTMap::new();
+
+    for e in &[5, -5, 2, -2, 6, -6] {
+        map.insert(*e, e * 10);
+    }
+
+    let pairs: Vec<(&i32, &i32)> = map.iter().collect();
+    assert_eq!(pairs, vec![(&-6, &-60), (&-5, &-50), (&-2, &-20), (&2, &20), (&5, &50), (&6, &60)]);
+
+    for (key, value) in &mut map {
+        *value += *key;
+    }
+
+    let mut keys = vec![];
+    for (key, value) in &map {
+        assert_eq!(*value, key * 11);
+        keys.push(*key);
+    }
+    assert_eq!(keys, vec![-6, -5, -2, 2, 5, 6]);
+
+    let owned: Vec<(i32, i32)> = map.into_iter().collect();
+    assert_eq!(owned, vec![(-6, -66), (-5, -55), (-2, -22), (2, 22), (5, 55), (6, 66)]);
+}
+
+#[test]
+fn test_map_iterators_from_both_ends() {
+    let mut map = BSTMap::new();
+    let mut oracle = BTreeMap::new();
+
+    for e in &[5, -5, 2, -2, 6, -6, 0] {
+        map.insert(*e, e * 10);
+        oracle.insert(*e, e * 10);
+    }
+
+    assert_eq!(map.iter().rev().collect::<Vec<_>>(), oracle.iter().rev().collect::<Vec<_>>());
+
+    let mut iter = map.iter();
+    assert_eq!(iter.next(), Some((&-6, &-60)));
+    assert_eq!(iter.next_back(), Some((&6, &60)));
+    assert_eq!(iter.next_back(), Some((&5, &50)));
+    assert_eq!(iter.next(), Some((&-5, &-50)));
+    assert_eq!(iter.collect::<Vec<_>>(), vec![(&-2, &-20), (&0, &0), (&2, &20)]);
+
+    for (key, value) in map.iter_mut().rev().take(3) {
+        *value += *key;
+    }
+    let mut iter = map.iter_mut();
+    *iter.next_back().unwrap().1 = 0;
+    *iter.next().unwrap().1 = 0;
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        vec![(&-6, &0), (&-5, &-50), (&-2, &-20), (&0, &0), (&2, &22), (&5, &55), (&6, &0)]
+    );
+
+    let mut owned = map.into_iter();
+    assert_eq!(owned.next_back(), Some((6, 0)));
+    assert_eq!(owned.next(), Some((-6, 0)));
+    let rest: Vec<(i32, i32)> = owned.rev().collect();
+    assert_eq!(rest, vec![(5, 55), (2, 22), (0, 0), (-2, -20), (-5, -50)]);
+}
+
+#[test]
+fn test_map_matches_oracle() {
+    let mut map = BSTMap::new();
+    let mut oracle = BTreeMap::new();
+    let mut state: u32 = 5;
+
+    for i in 0..5000 {
+        let key = next_key(&mut state, 300);
+
+        match state % 3 {
+            0 => assert_eq!(map.remove(&key), oracle.remove(&key)),
+            1 => assert_eq!(map.insert(key, i), oracle.insert(key, i)),
+            _ => {
+                *map.entry(key).or_insert(0) += i;
+                *oracle.entry(key).or_insert(0) += i;
+            }
+        }
+
+        assert_eq!(map.len(), oracle.len());
+    }
+
+    assert_eq!(map.iter().collect::<Vec<_>>(), oracle.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_range() {
+    let mut bst = hw03::second::BST::new();
+
+    for e in &[5, -5, 2, -2, 6, -6, 0] {
+        bst.insert(*e);
+    }
+
+    assert_eq!(bst.range(-2..5).cloned().collect::<Vec<_>>(), vec![-2, 0, 2]);
+    assert_eq!(bst.range(-3..=5).cloned().collect::<Vec<_>>(), vec![-2, 0, 2, 5]);
+    assert_eq!(bst.range(..0).cloned().collect::<Vec<_>>(), vec![-6, -5, -2]);
+    assert_eq!(bst.range(3..).rev().cloned().collect::<Vec<_>>(), vec![6, 5]);
+    assert_eq!(bst.range(..).count(), 7);
+    assert_eq!(bst.range(3..5).next(), None);
+
+    for e in bst.range_mut(1..) {
+        *e *= 10;
+    }
+    assert_eq!(bst.iter().cloned().collect::<Vec<_>>(), vec![-6, -5, -2, 0, 20, 50, 60]);
+}
+
+#[test]
+fn test_range_matches_oracle() {
+    let mut state: u32 = 17;
+    let mut next = || next_key(&mut state, 120) - 10;
+
+    for &balanced in &[false, true] {
+        let mut bst = if balanced {
+            hw03::second::BST::new_avl()
+        } else {
+            hw03::second::BST::new()
+        };
+        let mut oracle = BTreeSet::new();
+
+        for _ in 0..60 {
+            let e = next();
+            bst.insert(e);
+            oracle.insert(e);
+        }
+
+        for _ in 0..500 {
+            let (a, b) = (next(), next());
+            let (a, b) = (a.min(b), a.max(b));
+
+            let bounds = [
+                (Bound::Included(a), Bound::Included(b)),
+                (Bound::Included(a), Bound::Excluded(b)),
+                (Bound::Excluded(a), Bound::Included(b)),
+                (Bound::Unbounded, Bound::Excluded(b)),
+                (Bound::Excluded(a), Bound::Unbounded),
+            ];
+
+            for &range in &bounds {
+                let expected: Vec<i32> = oracle.range(range).cloned().collect();
+                assert_eq!(bst.range(range).cloned().collect::<Vec<_>>(), expected);
+                assert_eq!(
+                    bst.range(range).rev().cloned().collect::<Vec<_>>(),
+                    expected.iter().rev().cloned().collect::<Vec<_>>()
+                );
+                assert_eq!(bst.range_mut(range).count(), expected.len());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_owned_elements() {
+    let mut bst = hw03::second::BST::new_avl();
+
+    for word in "the quick brown fox jumps over the lazy dog".split(' ') {
+        bst.insert(word.to_string());
+    }
+
+    assert_eq!(bst.find("fox"), true);
+    assert_eq!(bst.find("cat"), false);
+    assert_eq!(bst.remove("the"), true);
+    assert_eq!(bst.remove("the"), false);
+
+    let range = (Bound::Included("f"), Bound::Excluded("p"));
+    assert_eq!(
+        bst.range::<str, _>(range).map(|s| s.as_str()).collect::<Vec<_>>(),
+        vec!["fox", "jumps", "lazy", "over"]
+    );
+
+    for word in bst.iter_mut() {
+        word.insert(0, '_');
+    }
+    assert_eq!(bst.iter().next().map(|s| s.as_str()), Some("_brown"));
+
+    let words: Vec<String> = bst.into_iter().rev().collect();
+    assert_eq!(words, vec!["_quick", "_over", "_lazy", "_jumps", "_fox", "_dog", "_brown"]);
+}
+
+#[test]
+fn test_select_rank() {
+    let mut bst = hw03::second::BST::new();
+
+    assert_eq!(bst.len(), 0);
+    assert_eq!(bst.is_empty(), true);
+    assert_eq!(bst.select(0), None);
+    assert_eq!(bst.rank(&3), 0);
+
+    for e in &[5, -5, 2, -2, 6, -6] {
+        bst.insert(*e);
+    }
+
+    assert_eq!(bst.len(), 6);
+    assert_eq!(bst.select(0), Some(&-6));
+    assert_eq!(bst.select(3), Some(&2));
+    assert_eq!(bst.select(5), Some(&6));
+    assert_eq!(bst.select(6), None);
+
+    assert_eq!(bst.rank(&-6), 0);
+    assert_eq!(bst.rank(&0), 3);
+    assert_eq!(bst.rank(&2), 3);
+    assert_eq!(bst.rank(&100), 6);
+}
+
+#[test]
+fn test_select_rank_matches_oracle() {
+    let mut state: u32 = 23;
+
+    for &balanced in &[false, true] {
+        let mut bst = if balanced {
+            hw03::second::BST::new_avl()
+        } else {
+            hw03::second::BST::new()
+        };
+        let mut oracle: Vec<i32> = vec![];
+
+        for _ in 0..3000 {
+            let e = next_key(&mut state, 400);
+
+            match oracle.binary_search(&e) {
+                Ok(i) if state & 3 == 0 => {
+                    assert_eq!(bst.remove(&e), true);
+                    oracle.remove(i);
+                }
+                Ok(_) => assert_eq!(bst.insert(e), false),
+                Err(i) => {
+                    assert_eq!(bst.insert(e), true);
+                    oracle.insert(i, e);
+                }
+            }
+
+            assert_eq!(bst.len(), oracle.len());
+            assert_eq!(bst.rank(&e), oracle.binary_search(&e).unwrap_or_else(|i| i));
+        }
+
+        assert_eq!(bst.is_valid(), true);
+        for (k, e) in oracle.iter().enumerate() {
+            assert_eq!(bst.select(k), Some(e));
+            assert_eq!(bst.rank(e), k);
+        }
+        assert_eq!(bst.select(oracle.len()), None);
+    }
+}
+
+fn random_bst(
+    balanced: bool,
+    state: &mut u32,
+    n: usize,
+) -> (hw03::second::BST<i32>, BTreeSet<i32>) {
+    let mut bst = if balanced {
+        hw03::second::BST::new_avl()
+    } else {
+        hw03::second::BST::new()
+    };
+    let mut oracle = BTreeSet::new();
+
+    for _ in 0..n {
+        let e = next_key(state, 600);
+        assert_eq!(bst.insert(e), oracle.insert(e));
+    }
+
+    (bst, oracle)
+}
+
+fn check_set(bst: &hw03::second::BST<i32>, oracle: &BTreeSet<i32>) {
+    assert_eq!(bst.is_valid(), true);
+    assert_eq!(bst.len(), oracle.len());
+    assert_eq!(bst.iter().collect::<Vec<_>>(), oracle.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_set_operations_match_oracle() {
+    let mut state: u32 = 29;
+
+    for &(a_balanced, b_balanced) in &[(false, false), (false, true), (true, false), (true, true)] {
+        for &(m, n) in &[(0, 0), (0, 50), (50, 0), (300, 300), (20, 500), (500, 20)] {
+            let (a, a_set) = random_bst(a_balanced, &mut state, m);
+            let (b, b_set) = random_bst(b_balanced, &mut state, n);
+            check_set(&a.union(b), &a_set.union(&b_set).cloned().collect());
+
+            let (a, a_set) = random_bst(a_balanced, &mut state, m);
+            let (b, b_set) = random_bst(b_balanced, &mut state, n);
+            check_set(&a.intersection(b), &a_set.intersection(&b_set).cloned().collect());
+
+            let (a, a_set) = random_bst(a_balanced, &mut state, m);
+            let (b, b_set) = random_bst(b_balanced, &mut state, n);
+            check_set(&a.difference(b), &a_set.difference(&b_set).cloned().collect());
+
+            let (a, a_set) = random_bst(a_balanced, &mut state, m);
+            let (b, b_set) = random_bst(b_balanced, &mut state, n);
+            let expected = a_set.symmetric_difference(&b_set).cloned().collect();
+            check_set(&a.symmetric_difference(b), &expected);
+        }
+    }
+}
+
+fn sorted_bst<I>(balanced: bool, keys: I) -> hw03::second::BST<i32>
+where
+    I: Iterator<Item = i32>,
+{
+    let mut bst = if balanced {
+        hw03::second::BST::new_avl()
+    } else {
+        hw03::second::BST::new()
+    };
+    for e in keys {
+        bst.insert(e);
+    }
+    bst
+}
+
+#[test]
+fn test_set_operations_on_degenerate_trees() {
+    // Inserting sorted keys into unbalanced trees builds a single long path,
+    // which has to be merged rather than split.
+    let ascending = || (0..2000).map(|e| 2 * e);
+    let descending = || (0..2000).rev().map(|e| 3 * e);
+    let a_set: BTreeSet<i32> = ascending().collect();
+    let b_set: BTreeSet<i32> = descending().collect();
+
+    for &balanced in &[false, true] {
+        let a = || sorted_bst(balanced, ascending());
+        let b = || sorted_bst(false, descending());
+
+        let union = a().union(b());
+        check_set(&union, &a_set.union(&b_set).cloned().collect());
+        assert_eq!(union.height() <= 13, true);
+
+        let intersection = a().intersection(b());
+        check_set(&intersection, &a_set.intersection(&b_set).cloned().collect());
+        let difference = a().difference(b());
+        check_set(&difference, &a_set.difference(&b_set).cloned().collect());
+        let symmetric_difference = a().symmetric_difference(b());
+        let expected = a_set.symmetric_difference(&b_set).cloned().collect();
+        check_set(&symmetric_difference, &expected);
+    }
+}
+
+#[test]
+fn test_split_at_and_join() {
+    let mut state: u32 = 31;
+
+    for &balanced in &[false, true] {
+        for &at in &[-1, 0, 150, 151, 300, 599, 1000] {
+            let (bst, mut oracle) = random_bst(balanced, &mut state, 400);
+            let upper = oracle.split_off(&at);
+
+            let (left, right) = bst.split_at(&at);
+            check_set(&left, &oracle);
+            check_set(&right, &upper);
+
+            let joined = left.join(right);
+            oracle.extend(upper);
+            check_set(&joined, &oracle);
+        }
+    }
+
+    // Joining a small AVL tree to a much higher one keeps it balanced.
+    let mut small = hw03::second::BST::new_avl();
+    small.insert(-1);
+    let mut large = hw03::second::BST::new_avl();
+    for e in 0..1000 {
+        large.insert(e);
+    }
+    let joined = small.join(large);
+    assert_eq!(joined.is_valid(), true);
+    assert_eq!(joined.len(), 1001);
+    assert_eq!(joined.height() <= 11, true);
+}
+
+#[test]
+#[should_panic(expected = "overlap")]
+fn test_join_overlapping() {
+    let mut a = hw03::second::BST::new();
+    a.insert(5);
+    let mut b = hw03::second::BST::new();
+    b.insert(3);
+    b.insert(7);
+    a.join(b);
+}
+
+#[test]
+fn test_persistent_versions() {
+    let empty = PersistentBST::new();
+    let one = empty.insert(5);
+    let two = one.insert(-5);
+    let three = two.insert(2);
+    let removed = three.remove(&5);
+
+    assert_eq!(empty.is_empty(), true);
+    assert_eq!(one.iter().cloned().collect::<Vec<_>>(), vec![5]);
+    assert_eq!(two.iter().cloned().collect::<Vec<_>>(), vec![-5, 5]);
+    assert_eq!(three.iter().cloned().collect::<Vec<_>>(), vec![-5, 2, 5]);
+    assert_eq!(removed.iter().cloned().collect::<Vec<_>>(), vec![-5, 2]);
+
+    assert_eq!(three.find(&5), true);
+    assert_eq!(removed.find(&5), false);
+    assert_eq!(removed.remove(&5).len(), 2);
+    assert_eq!(three.insert(2).len(), 3);
+
+    // Inserting 5, -5 and 2 rotates 2 up to the root.
+    assert_eq!(three.level_order().cloned().collect::<Vec<_>>(), vec![2, -5, 5]);
+    assert_eq!(three.iter().rev().cloned().collect::<Vec<_>>(), vec![5, 2, -5]);
+    assert_eq!(three.range(0..).cloned().collect::<Vec<_>>(), vec![2, 5]);
+
+    let mut sum = 0;
+    for e in &three {
+        sum += *e;
+    }
+    assert_eq!(sum, 2);
+}
+
+#[test]
+fn test_persistent_matches_oracle() {
+    let mut versions = vec![(PersistentBST::new(), BTreeSet::new())];
+    let mut state: u32 = 29;
+
+    for _ in 0..1000 {
+        let e = next_key(&mut state, 200);
+        // Branch off a random earlier version.
+        let (tree, oracle) = &versions[(state as usize >> 4) % versions.len()];
+        let mut oracle = oracle.clone();
+
+        let tree = if state & 3 == 0 {
+            oracle.remove(&e);
+            tree.remove(&e)
+        } else {
+            oracle.insert(e);
+            tree.insert(e)
+        };
+
+        assert_eq!(tree.is_valid(), true);
+        versions.push((tree, oracle));
+    }
+
+    for (tree, oracle) in &versions {
+        assert_eq!(tree.len(), oracle.len());
+        assert_eq!(tree.iter().collect::<Vec<_>>(), oracle.iter().collect::<Vec<_>>());
+        for e in 0..200 {
+            assert_eq!(tree.find(&e), oracle.contains(&e));
+        }
+    }
+}
+
+#[test]
+fn test_persistent_snapshot_across_threads() {
+    let snapshot: PersistentBST<String> = (0..100)
+        .map(|i| i.to_string())
+        .fold(PersistentBST::new(), |tree, e| tree.insert(e));
+
+    let shared = snapshot.clone();
+    let handle = std::thread::spawn(move || shared.find("42") && !shared.find("100"));
+
+    let updated = snapshot.remove("42");
+
+    assert_eq!(handle.join().unwrap(), true);
+    assert_eq!(snapshot.find("42"), true);
+    assert_eq!(updated.find("42"), false);
+    assert_eq!(updated.len(), 99);
+}
+
+#[test]
+fn test_concurrent_sequential() {
+    let bst = ConcurrentBST::new();
+
+    assert_eq!(bst.is_empty(), true);
+    for e in &[10, 5, 15, 3, 12, 20, 13] {
+        assert_eq!(bst.insert(*e), true);
+    }
+    assert_eq!(bst.insert(12), false);
+    assert_eq!(bst.len(), 7);
+
+    // 3 is a leaf, 12 has one child and 10 has two.
+    assert_eq!(bst.remove(&3), true);
+    assert_eq!(bst.remove(&12), true);
+    assert_eq!(bst.remove(&10), true);
+    assert_eq!(bst.remove(&10), false);
+
+    for e in &[3, 10, 12] {
+        assert_eq!(bst.find(e), false);
+    }
+    for e in &[5, 13, 15, 20] {
+        assert_eq!(bst.find(e), true);
+    }
+    assert_eq!(bst.len(), 4);
+    assert_eq!(bst.is_valid(), true);
+}
+
+/// An element whose copies cannot be made.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Uncloneable(i32);
+
+impl Clone for Uncloneable {
+    fn clone(&self) -> Uncloneable {
+        panic!("cannot clone {:?}", self);
+    }
+}
+
+#[test]
+fn test_concurrent_remove_panicking_clone() {
+    let bst = ConcurrentBST::new();
+    for e in &[10, 5, 15, 12, 20] {
+        bst.insert(Uncloneable(*e));
+    }
+
+    // Removing 10 copies its successor 12, which panics before any change.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| bst.remove(&Uncloneable(10))));
+    assert_eq!(result.is_err(), true);
+
+    assert_eq!(bst.len(), 5);
+    for e in &[5, 10, 12, 15, 20] {
+        assert_eq!(bst.find(&Uncloneable(*e)), true);
+    }
+    assert_eq!(bst.is_valid(), true);
+}
+
+#[test]
+fn test_concurrent_stress() {
+    const WRITERS: u32 = 8;
+    const KEYS: u32 = 2000;
+
+    let bst = Arc::new(ConcurrentBST::new());
+
+    // Each writer owns the keys congruent to its index, so its own view of
+    // them is a sequential oracle, while keys of all writers interleave in
+    // the tree.
+    let writers: Vec<_> = (0..WRITERS)
+        .map(|id| {
+            let bst = bst.clone();
+            thread::spawn(move || {
+                let mut oracle = BTreeSet::new();
+                let mut state = id + 1;
+
+                for _ in 0..20_000 {
+                    let key = next_key(&mut state, (KEYS / WRITERS) as i32) as u32 * WRITERS + id;
+
+                    if state & 0x80 == 0 {
+                        assert_eq!(bst.insert(key), oracle.insert(key));
+                    } else {
+                        assert_eq!(bst.remove(&key), oracle.remove(&key));
+                    }
+                }
+
+                oracle
+            })
+        })
+        .collect();
+
+    let readers: Vec<_> = (0..2)
+        .map(|_| {
+            let bst = bst.clone();
+            thread::spawn(move || {
+                for key in (0..KEYS).cycle().take(50_000) {
+                    bst.find(&key);
+                }
+            })
+        })
+        .collect();
+
+    let mut oracle = BTreeSet::new();
+    for writer in writers {
+        oracle.extend(writer.join().unwrap());
+    }
+    for reader in readers {
+        reader.join().unwrap();
+    }
+
+    assert_eq!(bst.is_valid(), true);
+    assert_eq!(bst.len(), oracle.len());
+    for key in 0..KEYS {
+        assert_eq!(bst.find(&key), oracle.contains(&key));
+    }
+}